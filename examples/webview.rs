@@ -103,6 +103,19 @@ impl Example {
                         CefAction::Created(browser_id) => Task::none(),
                         CefAction::Run(task) => task.map(move |msg| Message::Cef(id, msg)),
                         CefAction::Loaded(browser_id) => Task::none(),
+                        CefAction::Load(event) => Task::none(),
+                        CefAction::Display(event) => Task::none(),
+                        CefAction::PopupRequested(request) => Task::none(),
+                        CefAction::PopupOpened(request, popup) => Task::none(),
+                        CefAction::ContextMenu(event) => Task::none(),
+                        CefAction::Download(event) => Task::none(),
+                        CefAction::FileDialog(request) => Task::done(Message::Cef(
+                            id,
+                            CefMessage::FileDialogResponse(request.id, None),
+                        )),
+                        CefAction::JsDialog(event) => Task::none(),
+                        CefAction::Find(result) => Task::none(),
+                        CefAction::ZoomChanged(browser_id, factor) => Task::none(),
                         CefAction::Closed(browser_id) => {
                             cef::shutdown();
                             iced::exit()
@@ -0,0 +1,202 @@
+//! Rust functions exposed to javascript
+
+use cef::{ImplDictionaryValue, ImplFrame, ImplListValue};
+use std::collections::BTreeMap;
+
+pub(crate) const BINDINGS_KEY: &str = "icy.bindings";
+pub(crate) const INVOKE_REQUEST: &str = "renderer.invoke";
+pub(crate) const INVOKE_RESULT: &str = "browser.invoke_result";
+
+type Binding = std::rc::Rc<dyn Fn(serde_json::Value) -> Result<serde_json::Value, String>>;
+
+/// Named Rust functions installed on `window[namespace]` in every V8 context.
+///
+/// Each function takes its javascript arguments as a json array deserialized into
+/// `Args` and returns a `Promise` resolved with the serialized return value, or
+/// rejected with the error message.
+///
+/// Only the main frame gets the bindings, unless origins are allowed with
+/// [`JsBindings::allow_origin`], then every frame of those origins and no other.
+#[derive(Clone)]
+pub struct JsBindings {
+    namespace: String,
+    functions: BTreeMap<String, Binding>,
+    origins: Vec<String>,
+}
+
+/// Binding names and namespace handed to the render process on browser creation
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct BindingsConfig {
+    pub namespace: String,
+    pub names: Vec<String>,
+    pub origins: Vec<String>,
+}
+
+impl BindingsConfig {
+    pub(crate) fn allows(&self, url: &str, is_main_frame: bool) -> bool {
+        allows(&self.origins, url, is_main_frame)
+    }
+}
+
+/// Serialized origin of `url`, `None` for opaque origins like `data:` urls
+fn origin(url: &str) -> Option<String> {
+    let origin = url::Url::parse(url).ok()?.origin();
+    origin.is_tuple().then(|| origin.ascii_serialization())
+}
+
+/// Whether a frame showing `url` may call the bindings
+fn allows(origins: &[String], url: &str, is_main_frame: bool) -> bool {
+    if origins.is_empty() {
+        return is_main_frame;
+    }
+    origin(url).is_some_and(|origin| origins.contains(&origin))
+}
+
+impl Default for JsBindings {
+    fn default() -> Self {
+        Self::new("icy")
+    }
+}
+
+impl std::fmt::Debug for JsBindings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsBindings")
+            .field("namespace", &self.namespace)
+            .field("functions", &self.functions.keys().collect::<Vec<_>>())
+            .field("origins", &self.origins)
+            .finish()
+    }
+}
+
+impl JsBindings {
+    pub fn new(namespace: impl Into<String>) -> Self {
+        Self {
+            namespace: namespace.into(),
+            functions: BTreeMap::new(),
+            origins: Vec::new(),
+        }
+    }
+
+    /// Expose the bindings to frames of `origin`, e.g. `https://example.com`, instead of
+    /// the main frame of any page. Invalid origins are ignored.
+    pub fn allow_origin(mut self, origin: impl AsRef<str>) -> Self {
+        match self::origin(origin.as_ref()) {
+            Some(origin) => self.origins.push(origin),
+            None => tracing::warn!(origin = origin.as_ref(), "invalid binding origin"),
+        }
+        self
+    }
+
+    pub fn register<Args, Ret, F>(mut self, name: impl Into<String>, function: F) -> Self
+    where
+        Args: serde::de::DeserializeOwned,
+        Ret: serde::Serialize,
+        F: Fn(Args) -> Result<Ret, String> + 'static,
+    {
+        let binding = move |args: serde_json::Value| {
+            let args = serde_json::from_value(args).map_err(|err| err.to_string())?;
+            serde_json::to_value(function(args)?).map_err(|err| err.to_string())
+        };
+        self.functions
+            .insert(name.into(), std::rc::Rc::new(binding));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty()
+    }
+
+    pub(crate) fn extra_info(&self) -> Option<cef::DictionaryValue> {
+        if self.is_empty() {
+            return None;
+        }
+        let config = serde_json::to_string(&BindingsConfig {
+            namespace: self.namespace.clone(),
+            names: self.functions.keys().cloned().collect(),
+            origins: self.origins.clone(),
+        })
+        .ok()?;
+        let extra_info = cef::dictionary_value_create()?;
+        if extra_info.set_string(Some(&BINDINGS_KEY.into()), Some(&config.as_str().into())) != 1 {
+            return None;
+        }
+        Some(extra_info)
+    }
+
+    fn invoke(&self, name: &str, args: &str) -> Result<String, String> {
+        let Some(binding) = self.functions.get(name) else {
+            return Err(format!("no binding named {name}"));
+        };
+        let args = serde_json::from_str(args).map_err(|err| err.to_string())?;
+        binding(args).map(|value| value.to_string())
+    }
+
+    /// Run the binding requested by the render process and reply to the calling frame.
+    pub(crate) fn dispatch(&self, frame: &cef::Frame, message: &cef::ProcessMessage) -> bool {
+        let Some(args) = message.argument_list() else {
+            return false;
+        };
+        let call_id = args.int(0);
+        let name = cef::CefString::from(&args.string(1)).to_string();
+        let payload = cef::CefString::from(&args.string(2)).to_string();
+
+        // the render process is not trusted to have installed the bindings in the right frames
+        let url = cef::CefString::from(&frame.url()).to_string();
+        let result = if allows(&self.origins, &url, frame.is_main() == 1) {
+            self.invoke(&name, &payload)
+        } else {
+            tracing::warn!(name, url, "binding called from a frame that is not allowed");
+            Err(format!("binding {name} is not allowed in this frame"))
+        };
+        let (success, result) = match result {
+            Ok(result) => (true, result),
+            Err(err) => (false, err),
+        };
+
+        let Some(mut reply) = cef::process_message_create(Some(&INVOKE_RESULT.into())) else {
+            return false;
+        };
+        let Some(reply_args) = reply.argument_list() else {
+            return false;
+        };
+        if reply_args.set_int(0, call_id) != 1
+            || reply_args.set_bool(1, success as _) != 1
+            || reply_args.set_string(2, Some(&result.as_str().into())) != 1
+        {
+            return false;
+        }
+        frame.send_process_message(
+            cef::sys::cef_process_id_t::PID_RENDERER.into(),
+            Some(&mut reply),
+        );
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn main_frame_only_without_origins() {
+        assert!(allows(&[], "https://example.com/", true));
+        assert!(!allows(&[], "https://example.com/", false));
+    }
+
+    #[test]
+    fn allowed_origins() {
+        let origins = [origin("https://example.com/app").unwrap()];
+        let cases = [
+            ("https://example.com/", true, true),
+            ("https://example.com/frame.html", false, true),
+            ("https://example.com:8443/", true, false),
+            ("http://example.com/", true, false),
+            ("https://example.com.evil.test/", true, false),
+            ("data:text/html,hi", true, false),
+            ("not a url", true, false),
+        ];
+        for (url, is_main_frame, expected) in cases {
+            assert_eq!(allows(&origins, url, is_main_frame), expected, "{url}");
+        }
+    }
+}
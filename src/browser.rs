@@ -8,7 +8,12 @@ use cef::{
 use std::{cell::RefCell, collections::BTreeMap, time::Duration};
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
 
-use crate::{BrowserId, BrowserProcessMessage, IcyClientState, instance::LaunchId};
+use crate::{
+    BrowserId, BrowserProcessMessage, IcyClientState,
+    instance::LaunchId,
+    scheme::{CustomScheme, add_custom_schemes},
+    settings::CommandLineSwitches,
+};
 
 #[derive(Clone, Debug)]
 pub struct IcyCefApp {
@@ -58,14 +63,23 @@ pub(crate) struct AppBuilder {
     object: *mut RcImpl<cef::sys::_cef_app_t, Self>,
     app: IcyCefApp,
     browser_handler: cef::BrowserProcessHandler,
+    schemes: Vec<CustomScheme>,
+    switches: CommandLineSwitches,
 }
 
 impl AppBuilder {
-    pub(crate) fn build(app: IcyCefApp, browser_handler: IcyBrowserProcessHandler) -> cef::App {
+    pub(crate) fn build(
+        app: IcyCefApp,
+        browser_handler: IcyBrowserProcessHandler,
+        schemes: Vec<CustomScheme>,
+        switches: CommandLineSwitches,
+    ) -> cef::App {
         cef::App::new(Self {
             object: std::ptr::null_mut(),
             app,
-            browser_handler: BrowserProcessHandlerBuilder::build(browser_handler),
+            browser_handler: BrowserProcessHandlerBuilder::build(browser_handler, switches.clone()),
+            schemes,
+            switches,
         })
     }
 }
@@ -87,6 +101,8 @@ impl Clone for AppBuilder {
             object,
             app: self.app.clone(),
             browser_handler: self.browser_handler.clone(),
+            schemes: self.schemes.clone(),
+            switches: self.switches.clone(),
         }
     }
 }
@@ -110,34 +126,29 @@ impl ImplApp for AppBuilder {
         process_type: Option<&cef::CefStringUtf16>,
         command_line: Option<&mut cef::CommandLine>,
     ) {
-        if let Some(process_type) = process_type {
-            tracing::info!(
-                process_type = cef::CefStringUtf8::from(process_type).to_string(),
-                "process launching",
-            );
+        let process_type = process_type
+            .map(|process_type| cef::CefStringUtf8::from(process_type).to_string())
+            .unwrap_or_default();
+        if !process_type.is_empty() {
+            tracing::info!(process_type, "process launching");
+            // chromium passes the switches child processes need on to them
+            return;
         }
         let Some(command_line) = command_line else {
             tracing::error!("no command line");
             return;
         };
 
-        command_line.append_switch(Some(&"disable-javascript-open-windows".into()));
-        command_line.append_switch(Some(&"disable-web-security".into()));
-        command_line.append_switch(Some(&"hide-crash-restore-bubble".into()));
-        command_line.append_switch(Some(&"disable-chrome-login-prompt".into()));
-        command_line.append_switch(Some(&"allow-running-insecure-content".into()));
-        command_line.append_switch(Some(&"no-startup-window".into()));
-        command_line.append_switch(Some(&"disable-popup-blocking".into()));
-        command_line.append_switch(Some(&"noerrdialogs".into()));
-        command_line.append_switch(Some(&"hide-crash-restore-bubble".into()));
-        command_line.append_switch(Some(&"use-mock-keychain".into()));
-        command_line.append_switch(Some(&"disable-spell-checking".into()));
-        command_line.append_switch(Some(&"disable-session-crashed-bubble".into()));
-        command_line
-            .append_switch_with_value(Some(&"remote-debugging-port".into()), Some(&"9229".into()));
+        self.switches.apply(command_line);
         tracing::info!("pre-set command line done");
     }
 
+    fn on_register_custom_schemes(&self, registrar: Option<&mut cef::SchemeRegistrar>) {
+        if let Some(registrar) = registrar {
+            add_custom_schemes(registrar, &self.schemes);
+        }
+    }
+
     fn browser_process_handler(&self) -> Option<cef::BrowserProcessHandler> {
         Some(self.browser_handler.clone())
     }
@@ -158,13 +169,18 @@ impl IcyBrowserProcessHandler {
 pub(crate) struct BrowserProcessHandlerBuilder {
     object: *mut RcImpl<sys::cef_browser_process_handler_t, Self>,
     handler: IcyBrowserProcessHandler,
+    switches: CommandLineSwitches,
 }
 
 impl BrowserProcessHandlerBuilder {
-    pub(crate) fn build(handler: IcyBrowserProcessHandler) -> BrowserProcessHandler {
+    pub(crate) fn build(
+        handler: IcyBrowserProcessHandler,
+        switches: CommandLineSwitches,
+    ) -> BrowserProcessHandler {
         BrowserProcessHandler::new(Self {
             object: std::ptr::null_mut(),
             handler,
+            switches,
         })
     }
 }
@@ -195,6 +211,7 @@ impl Clone for BrowserProcessHandlerBuilder {
         Self {
             object,
             handler: self.handler.clone(),
+            switches: self.switches.clone(),
         }
     }
 }
@@ -215,15 +232,7 @@ impl ImplBrowserProcessHandler for BrowserProcessHandlerBuilder {
             return;
         };
 
-        command_line.append_switch(Some(&"disable-javascript-open-windows".into()));
-        command_line.append_switch(Some(&"disable-web-security".into()));
-        command_line.append_switch(Some(&"hide-crash-restore-bubble".into()));
-        command_line.append_switch(Some(&"disable-chrome-login-prompt".into()));
-        command_line.append_switch(Some(&"allow-running-insecure-content".into()));
-        command_line.append_switch(Some(&"hide-crash-restore-bubble".into()));
-        command_line.append_switch(Some(&"disable-session-crashed-bubble".into()));
-        command_line.append_switch(Some(&"ignore-certificate-errors".into()));
-        command_line.append_switch(Some(&"ignore-ssl-errors".into()));
+        self.switches.apply(command_line);
     }
 
     fn on_schedule_message_pump_work(&self, delay_ms: i64) {
@@ -1,39 +1,93 @@
 use cef::{self, DisplayHandler, ImplBrowser};
 use cef::{
-    Client, ContextMenuHandler, ImplClient, ImplProcessMessage, KeyboardHandler, LifeSpanHandler,
-    LoadHandler, RenderHandler, WrapClient,
+    Client, ContextMenuHandler, DialogHandler, DownloadHandler, FindHandler, ImplClient,
+    ImplProcessMessage, JsdialogHandler, KeyboardHandler, LifeSpanHandler, LoadHandler,
+    RenderHandler, RequestHandler, WrapClient,
     rc::{Rc, RcImpl},
     sys,
 };
-use context_menu_handler::{ContextMenuHandlerBuilder, IcyContextMenuHandler};
+use context_menu_handler::{
+    ContextMenuBuilder, ContextMenuHandlerBuilder, IcyContextMenuHandler, IcyContextMenuState,
+};
+use dialog_handler::{DialogHandlerBuilder, IcyDialogHandler, IcyDialogState};
+use download_handler::{DownloadHandlerBuilder, IcyDownloadHandler, IcyDownloadState};
+use find_handler::{FindHandlerBuilder, IcyFindHandler};
+use jsdialog_handler::{IcyJsDialogHandler, IcyJsDialogState, JsDialogHandlerBuilder};
 use keyboard_handler::{IcyKeyboardHandler, IcyKeyboardState, KeyboardHandlerBuilder};
 use lifespan_handler::{IcyLifeSpanHandler, LifeSpanHandlerBuilder};
 
 use load_handler::{IcyLoadHandler, LoadHandlerBuilder};
 use render_handler::{IcyRenderHandler, IcyRenderState, RenderHandlerBuilder};
-use std::ptr::null_mut;
-use tokio::sync::mpsc::{Receiver, UnboundedReceiver, UnboundedSender, unbounded_channel};
+use request_handler::{IcyRequestHandler, RequestHandlerBuilder};
+use std::{ptr::null_mut, sync::Arc};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
 
 use crate::{
+    bindings::{INVOKE_REQUEST, JsBindings},
     client::display_handler::{DisplayHandlerBuilder, IcyDisplayHandler, IcyDisplayState},
+    devtools::IcyDevToolsState,
+    eval::{EVAL_RESULT, IcyEvalState},
     instance::LaunchId,
+    profile::Profile,
 };
 mod context_menu_handler;
+mod dialog_handler;
 mod display_handler;
+mod download_handler;
+mod find_handler;
+mod jsdialog_handler;
 mod keyboard_handler;
 mod lifespan_handler;
 mod load_handler;
 mod render_handler;
+mod request_handler;
 
-pub use lifespan_handler::LifeSpanEvent;
+pub use context_menu_handler::{
+    ContextMenu, ContextMenuEvent, ContextMenuItem, ContextMenuParams, EditFlags, MediaType,
+    MenuCommand,
+};
+pub use dialog_handler::{
+    FileDialogMode, FileDialogProvider, FileDialogRequest, FileDialogResponse,
+};
+pub use display_handler::DisplayEvent;
+pub use download_handler::{DownloadCommand, DownloadEvent, DownloadPolicy};
+pub use find_handler::FindResult;
+pub use jsdialog_handler::{JsDialog, JsDialogEvent, JsDialogKind};
+pub use lifespan_handler::{
+    LifeSpanEvent, PopupAction, PopupFeatures, PopupPolicy, PopupRequest, PopupWebview,
+    WindowDisposition,
+};
 pub use load_handler::LoadEvent;
 
 pub use render_handler::CefFrame;
+pub use request_handler::{
+    InterceptedRequest, InterceptedResponse, RequestAction, RequestInterceptor, ResourceType,
+};
+
+/// Per-webview configuration applied when its browser is created
+#[derive(Clone, Default)]
+pub struct WebviewOptions {
+    pub(crate) bindings: JsBindings,
+    pub(crate) request_interceptor: Option<Arc<dyn RequestInterceptor>>,
+    pub(crate) profile: Option<Profile>,
+    pub(crate) popup_policy: Option<PopupPolicy>,
+    pub(crate) context_menu: Option<ContextMenuBuilder>,
+    pub(crate) download_policy: DownloadPolicy,
+    pub(crate) file_dialog: Option<std::rc::Rc<dyn FileDialogProvider>>,
+    /// Forward javascript dialogs as [`JsDialogEvent`], they are suppressed otherwise
+    pub(crate) js_dialogs: bool,
+}
 
 pub struct ClientEventSubscriber {
-    pub lifespan_rx: Receiver<LifeSpanEvent>,
+    pub lifespan_rx: UnboundedReceiver<LifeSpanEvent>,
     pub load_rx: UnboundedReceiver<LoadEvent>,
+    pub display_rx: UnboundedReceiver<DisplayEvent>,
+    pub context_menu_rx: UnboundedReceiver<ContextMenuEvent>,
     pub process_message_rx: UnboundedReceiver<CefIpcMessage>,
+    pub download_rx: UnboundedReceiver<DownloadEvent>,
+    pub file_dialog_rx: UnboundedReceiver<FileDialogRequest>,
+    pub js_dialog_rx: UnboundedReceiver<JsDialogEvent>,
+    pub find_rx: UnboundedReceiver<FindResult>,
     pub render_rx: UnboundedReceiver<CefFrame>,
 }
 
@@ -47,6 +101,12 @@ pub struct IcyClientState {
     pub render: IcyRenderState,
     pub keyboard: IcyKeyboardState,
     pub display: IcyDisplayState,
+    pub eval: IcyEvalState,
+    pub context_menu: IcyContextMenuState,
+    pub download: IcyDownloadState,
+    pub dialog: IcyDialogState,
+    pub js_dialog: IcyJsDialogState,
+    pub devtools: IcyDevToolsState,
 }
 
 impl IcyClient {
@@ -54,34 +114,72 @@ impl IcyClient {
         launch_id: LaunchId,
         device_scale_factor: f32,
         view_rect: cef::Rect,
+        options: WebviewOptions,
     ) -> (Self, IcyClientHandlers) {
+        let (lifespan_handler, lifespan_rx) = IcyLifeSpanHandler::new(launch_id, options.clone());
+        let WebviewOptions {
+            file_dialog,
+            download_policy,
+            bindings,
+            request_interceptor,
+            context_menu,
+            js_dialogs,
+            ..
+        } = options;
         let (load_handler, load_rx) = IcyLoadHandler::new();
-        let (display_handler, display_state) = IcyDisplayHandler::new();
+        let (display_handler, display_state, display_rx) = IcyDisplayHandler::new();
         let (render_handler, render_state, render_rx) =
             IcyRenderHandler::new(device_scale_factor, view_rect);
-        let (lifespan_handler, lifespan_rx) = IcyLifeSpanHandler::new(launch_id);
-        let context_menu_handler = IcyContextMenuHandler::new();
+        let (context_menu_handler, context_menu_state, context_menu_rx) =
+            IcyContextMenuHandler::new(context_menu);
+        let request_handler = request_interceptor.map(IcyRequestHandler::new);
+        let (download_handler, download_state, download_rx) =
+            IcyDownloadHandler::new(download_policy);
+        let (dialog_handler, dialog_state, file_dialog_rx) = IcyDialogHandler::new(file_dialog);
+        let (jsdialog_handler, js_dialog_state, js_dialog_rx) = IcyJsDialogHandler::new(js_dialogs);
+        let (find_handler, find_rx) = IcyFindHandler::new();
         let (keyboard_handler, keyboard_state) = IcyKeyboardHandler::new();
         let (process_message_tx, process_message_rx) = unbounded_channel();
+        let eval = IcyEvalState::default();
         let state = IcyClientState {
-            render: render_state,
+            render: render_state.clone(),
             keyboard: keyboard_state,
             display: display_state,
+            eval: eval.clone(),
+            context_menu: context_menu_state,
+            download: download_state,
+            dialog: dialog_state,
+            js_dialog: js_dialog_state,
+            devtools: IcyDevToolsState::default(),
         };
         let subscribers = ClientEventSubscriber {
+            find_rx,
+            js_dialog_rx,
+            file_dialog_rx,
+            download_rx,
             lifespan_rx,
             load_rx,
+            display_rx,
+            context_menu_rx,
             render_rx,
             process_message_rx,
         };
         let handlers = IcyClientHandlers {
+            find_handler,
+            jsdialog_handler,
+            dialog_handler,
+            download_handler,
             load_handler,
             lifespan_handler,
             render_handler,
             context_menu_handler,
             display_handler,
             keyboard_handler,
+            request_handler,
             process_message_tx,
+            eval,
+            bindings,
+            render: render_state,
         };
         (Self { state, subscribers }, handlers)
     }
@@ -89,22 +187,35 @@ impl IcyClient {
 
 #[derive(Clone)]
 pub struct IcyClientHandlers {
+    find_handler: IcyFindHandler,
+    jsdialog_handler: IcyJsDialogHandler,
+    dialog_handler: IcyDialogHandler,
+    download_handler: IcyDownloadHandler,
     load_handler: IcyLoadHandler,
     lifespan_handler: IcyLifeSpanHandler,
     render_handler: IcyRenderHandler,
     context_menu_handler: IcyContextMenuHandler,
     keyboard_handler: IcyKeyboardHandler,
     display_handler: IcyDisplayHandler,
+    request_handler: Option<IcyRequestHandler>,
     process_message_tx: UnboundedSender<CefIpcMessage>,
+    eval: IcyEvalState,
+    bindings: JsBindings,
+    render: IcyRenderState,
+}
+
+impl IcyClientHandlers {
+    /// Handlers of a popup browser created by `opener`
+    pub(crate) fn opened_by(&mut self, opener: &IcyLifeSpanHandler) {
+        self.lifespan_handler.opened_by(opener);
+    }
 }
 
 pub enum CefIpcMessage {
     FocusedNodeChanged {
         browser_id: i32,
-        x: f32,
-        y: f32,
-        width: i32,
-        height: i32,
+        /// in logical pixels of the view
+        bounds: iced::Rectangle,
     },
     CaretPositionChanged {
         browser_id: i32,
@@ -114,41 +225,70 @@ pub enum CefIpcMessage {
 
 pub(crate) struct ClientBuilder {
     object: *mut RcImpl<sys::cef_client_t, Self>,
+    find_handler: FindHandler,
+    jsdialog_handler: JsdialogHandler,
+    dialog_handler: DialogHandler,
+    download_handler: DownloadHandler,
     load_handler: LoadHandler,
     lifespan_handler: LifeSpanHandler,
     render_handler: RenderHandler,
     context_menu_handler: ContextMenuHandler,
     display_handler: DisplayHandler,
     keyboard_handler: KeyboardHandler,
+    request_handler: Option<RequestHandler>,
     process_message_tx: UnboundedSender<CefIpcMessage>,
+    eval: IcyEvalState,
+    bindings: JsBindings,
+    render: IcyRenderState,
 }
 
 impl ClientBuilder {
     pub(crate) fn build(client_handlers: IcyClientHandlers) -> Client {
         let IcyClientHandlers {
+            find_handler,
+            jsdialog_handler,
+            dialog_handler,
+            download_handler,
             load_handler,
             lifespan_handler,
             render_handler,
             context_menu_handler,
             keyboard_handler,
             display_handler,
+            request_handler,
             process_message_tx,
+            eval,
+            bindings,
+            render,
         } = client_handlers;
         let load_handler = LoadHandlerBuilder::build(load_handler);
+        let find_handler = FindHandlerBuilder::build(find_handler);
+        let jsdialog_handler = JsDialogHandlerBuilder::build(jsdialog_handler);
+        let dialog_handler = DialogHandlerBuilder::build(dialog_handler);
+        let download_handler = DownloadHandlerBuilder::build(download_handler);
         let lifespan_handler = LifeSpanHandlerBuilder::build(lifespan_handler);
         let render_handler = RenderHandlerBuilder::build(render_handler);
         let context_menu_handler = ContextMenuHandlerBuilder::build(context_menu_handler);
         let keyboard_handler = KeyboardHandlerBuilder::build(keyboard_handler);
         let display_handler = DisplayHandlerBuilder::build(display_handler);
+        let request_handler = request_handler.map(RequestHandlerBuilder::build);
         Client::new(Self {
             object: null_mut(),
+            find_handler,
+            jsdialog_handler,
+            dialog_handler,
+            download_handler,
             load_handler,
             display_handler,
             lifespan_handler,
             render_handler,
             context_menu_handler,
             keyboard_handler,
+            request_handler,
             process_message_tx,
+            eval,
+            bindings,
+            render,
         })
     }
 }
@@ -179,12 +319,20 @@ impl Clone for ClientBuilder {
         Self {
             object,
             load_handler: self.load_handler.clone(),
+            find_handler: self.find_handler.clone(),
+            jsdialog_handler: self.jsdialog_handler.clone(),
+            dialog_handler: self.dialog_handler.clone(),
+            download_handler: self.download_handler.clone(),
             render_handler: self.render_handler.clone(),
             lifespan_handler: self.lifespan_handler.clone(),
             context_menu_handler: self.context_menu_handler.clone(),
             keyboard_handler: self.keyboard_handler.clone(),
             display_handler: self.display_handler.clone(),
+            request_handler: self.request_handler.clone(),
             process_message_tx: self.process_message_tx.clone(),
+            eval: self.eval.clone(),
+            bindings: self.bindings.clone(),
+            render: self.render.clone(),
         }
     }
 }
@@ -218,10 +366,30 @@ impl ImplClient for ClientBuilder {
         Some(self.display_handler.clone())
     }
 
+    fn download_handler(&self) -> Option<DownloadHandler> {
+        Some(self.download_handler.clone())
+    }
+
+    fn dialog_handler(&self) -> Option<DialogHandler> {
+        Some(self.dialog_handler.clone())
+    }
+
+    fn jsdialog_handler(&self) -> Option<JsdialogHandler> {
+        Some(self.jsdialog_handler.clone())
+    }
+
+    fn find_handler(&self) -> Option<FindHandler> {
+        Some(self.find_handler.clone())
+    }
+
+    fn request_handler(&self) -> Option<RequestHandler> {
+        self.request_handler.clone()
+    }
+
     fn on_process_message_received(
         &self,
         browser: Option<&mut cef::Browser>,
-        _frame: Option<&mut cef::Frame>,
+        frame: Option<&mut cef::Frame>,
         _source_process: cef::ProcessId,
         message: Option<&mut cef::ProcessMessage>,
     ) -> std::ffi::c_int {
@@ -234,6 +402,20 @@ impl ImplClient for ClientBuilder {
         };
         let event_name =
             cef::CefStringUtf8::from(&cef::CefString::from(&message.name())).to_string();
+        if EVAL_RESULT == &event_name {
+            let Some(args) = message.argument_list() else {
+                return false as _;
+            };
+            let payload = cef::CefStringUtf8::from(&cef::CefString::from(&args.string(1)));
+            self.eval.resolve(args.int(0), &payload.to_string());
+            return true as _;
+        }
+        if INVOKE_REQUEST == &event_name {
+            let Some(frame) = frame else {
+                return false as _;
+            };
+            return self.bindings.dispatch(frame, message) as _;
+        }
         if "renderer.caret_offset_changed" == &event_name {
             if let Some(bound) = message.argument_list().map(|args| args.string(0)) {
                 #[derive(serde::Deserialize)]
@@ -277,14 +459,21 @@ impl ImplClient for ClientBuilder {
                 else {
                     return false as _;
                 };
+                // the render process measures in device pixels
+                let bounds = crate::geometry::device_to_view(
+                    iced::Rectangle {
+                        x,
+                        y,
+                        width,
+                        height,
+                    },
+                    self.render.device_scale_factor(),
+                );
                 if let Err(err) = self
                     .process_message_tx
                     .send(CefIpcMessage::FocusedNodeChanged {
                         browser_id: browser.identifier(),
-                        x: x / 2.0,
-                        y: y / 2.0,
-                        width: (height / 2.0).floor() as _,
-                        height: (width / 2.0).floor() as _,
+                        bounds,
                     })
                 {
                     tracing::error!(?err, "cannot send ipc message event");
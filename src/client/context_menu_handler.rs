@@ -4,17 +4,274 @@ use cef::{
     rc::{Rc, RcImpl},
     sys, *,
 };
-use std::ptr::null_mut;
+use std::{cell::RefCell, ptr::null_mut};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+use crate::BrowserId;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MediaType {
+    #[default]
+    None,
+    Image,
+    Video,
+    Audio,
+    Canvas,
+    File,
+    Plugin,
+}
+
+impl From<cef::ContextMenuMediaType> for MediaType {
+    fn from(media_type: cef::ContextMenuMediaType) -> Self {
+        use cef::sys::cef_context_menu_media_type_t;
+        match media_type.as_ref() {
+            cef_context_menu_media_type_t::CM_MEDIATYPE_IMAGE => Self::Image,
+            cef_context_menu_media_type_t::CM_MEDIATYPE_VIDEO => Self::Video,
+            cef_context_menu_media_type_t::CM_MEDIATYPE_AUDIO => Self::Audio,
+            cef_context_menu_media_type_t::CM_MEDIATYPE_CANVAS => Self::Canvas,
+            cef_context_menu_media_type_t::CM_MEDIATYPE_FILE => Self::File,
+            cef_context_menu_media_type_t::CM_MEDIATYPE_PLUGIN => Self::Plugin,
+            _ => Self::None,
+        }
+    }
+}
+
+/// Edit commands available in an editable node
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EditFlags {
+    pub can_undo: bool,
+    pub can_redo: bool,
+    pub can_cut: bool,
+    pub can_copy: bool,
+    pub can_paste: bool,
+    pub can_delete: bool,
+    pub can_select_all: bool,
+}
+
+impl From<cef::ContextMenuEditStateFlags> for EditFlags {
+    fn from(flags: cef::ContextMenuEditStateFlags) -> Self {
+        use cef::sys::cef_context_menu_edit_state_flags_t as flag;
+        let bits = flags.as_ref().0;
+        let has = |f: flag| bits & f.0 != 0;
+        Self {
+            can_undo: has(flag::CM_EDITFLAG_CAN_UNDO),
+            can_redo: has(flag::CM_EDITFLAG_CAN_REDO),
+            can_cut: has(flag::CM_EDITFLAG_CAN_CUT),
+            can_copy: has(flag::CM_EDITFLAG_CAN_COPY),
+            can_paste: has(flag::CM_EDITFLAG_CAN_PASTE),
+            can_delete: has(flag::CM_EDITFLAG_CAN_DELETE),
+            can_select_all: has(flag::CM_EDITFLAG_CAN_SELECT_ALL),
+        }
+    }
+}
+
+/// What was right-clicked, coordinates are relative to the view
+#[derive(Clone, Debug, Default)]
+pub struct ContextMenuParams {
+    pub x: i32,
+    pub y: i32,
+    pub page_url: String,
+    pub frame_url: String,
+    pub link_url: Option<String>,
+    pub source_url: Option<String>,
+    pub selection_text: Option<String>,
+    pub misspelled_word: Option<String>,
+    pub media_type: MediaType,
+    pub has_image_contents: bool,
+    pub is_editable: bool,
+    pub edit_flags: EditFlags,
+}
+
+impl From<&cef::ContextMenuParams> for ContextMenuParams {
+    fn from(params: &cef::ContextMenuParams) -> Self {
+        let non_empty = |value: String| Some(value).filter(|value| !value.is_empty());
+        Self {
+            x: params.xcoord(),
+            y: params.ycoord(),
+            page_url: CefString::from(&params.page_url()).to_string(),
+            frame_url: CefString::from(&params.frame_url()).to_string(),
+            link_url: non_empty(CefString::from(&params.link_url()).to_string()),
+            source_url: non_empty(CefString::from(&params.source_url()).to_string()),
+            selection_text: non_empty(CefString::from(&params.selection_text()).to_string()),
+            misspelled_word: non_empty(CefString::from(&params.misspelled_word()).to_string()),
+            media_type: params.media_type().into(),
+            has_image_contents: params.has_image_contents() == 1,
+            is_editable: params.is_editable() == 1,
+            edit_flags: params.edit_state_flags().into(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuCommand {
+    /// Command handled by cef, e.g. copy or reload
+    Builtin(i32),
+    /// Command defined by the application, reported back with [`ContextMenuEvent::Command`]
+    Custom(u32),
+}
+
+impl MenuCommand {
+    /// Largest id of [`MenuCommand::Custom`], cef reserves a small range for application commands
+    pub const MAX_CUSTOM_ID: u32 = (sys::cef_menu_id_t::MENU_ID_USER_LAST as i32
+        - sys::cef_menu_id_t::MENU_ID_USER_FIRST as i32) as u32;
+
+    fn from_id(id: i32) -> Self {
+        let user_first = sys::cef_menu_id_t::MENU_ID_USER_FIRST as i32;
+        let user_last = sys::cef_menu_id_t::MENU_ID_USER_LAST as i32;
+        if (user_first..=user_last).contains(&id) {
+            Self::Custom((id - user_first) as _)
+        } else {
+            Self::Builtin(id)
+        }
+    }
+
+    /// `None` for custom ids past [`MenuCommand::MAX_CUSTOM_ID`]
+    fn id(&self) -> Option<i32> {
+        match self {
+            Self::Builtin(id) => Some(*id),
+            Self::Custom(id) => (*id <= Self::MAX_CUSTOM_ID)
+                .then(|| sys::cef_menu_id_t::MENU_ID_USER_FIRST as i32 + *id as i32),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ContextMenuItem {
+    Command {
+        command: MenuCommand,
+        label: String,
+        enabled: bool,
+    },
+    Separator,
+}
+
+impl ContextMenuItem {
+    /// # Panics
+    ///
+    /// If `id` is greater than [`MenuCommand::MAX_CUSTOM_ID`].
+    pub fn custom(id: u32, label: impl Into<String>) -> Self {
+        assert!(
+            id <= MenuCommand::MAX_CUSTOM_ID,
+            "custom menu id {id} is greater than {}",
+            MenuCommand::MAX_CUSTOM_ID
+        );
+        Self::Command {
+            command: MenuCommand::Custom(id),
+            label: label.into(),
+            enabled: true,
+        }
+    }
+}
+
+/// Rewrites the default items of a context menu before it is shown
+pub type ContextMenuBuilder = std::rc::Rc<dyn Fn(&ContextMenuParams, &mut Vec<ContextMenuItem>)>;
+
+#[derive(Clone, Debug)]
+pub struct ContextMenu {
+    pub browser_id: BrowserId,
+    pub params: ContextMenuParams,
+    pub items: Vec<ContextMenuItem>,
+}
+
+#[derive(Clone, Debug)]
+pub enum ContextMenuEvent {
+    /// Render the menu and answer with [`crate::CefMessage::ContextMenuCommand`]
+    Show(ContextMenu),
+    Command {
+        browser_id: BrowserId,
+        id: u32,
+    },
+    Dismissed {
+        browser_id: BrowserId,
+    },
+}
+
+#[derive(Clone, Default)]
+pub struct IcyContextMenuState {
+    callback: std::rc::Rc<RefCell<Option<cef::RunContextMenuCallback>>>,
+}
+
+impl std::fmt::Debug for IcyContextMenuState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IcyContextMenuState")
+            .field("pending", &self.callback.borrow().is_some())
+            .finish()
+    }
+}
+
+impl IcyContextMenuState {
+    /// Run `command` of the pending menu, or dismiss it on `None`
+    pub(crate) fn select(&self, command: Option<MenuCommand>) {
+        let Some(callback) = self.callback.borrow_mut().take() else {
+            return;
+        };
+        // out of range custom ids dismiss the menu
+        match command.and_then(|command| command.id()) {
+            Some(id) => callback.cont(id, sys::cef_event_flags_t::EVENTFLAG_NONE.into()),
+            None => callback.cancel(),
+        }
+    }
+}
 
 #[derive(Clone)]
-pub struct IcyContextMenuHandler {}
+pub struct IcyContextMenuHandler {
+    state: IcyContextMenuState,
+    builder: Option<ContextMenuBuilder>,
+    tx: UnboundedSender<ContextMenuEvent>,
+}
 
 impl IcyContextMenuHandler {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(
+        builder: Option<ContextMenuBuilder>,
+    ) -> (
+        Self,
+        IcyContextMenuState,
+        UnboundedReceiver<ContextMenuEvent>,
+    ) {
+        let state = IcyContextMenuState::default();
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        (
+            Self {
+                state: state.clone(),
+                builder,
+                tx,
+            },
+            state,
+            rx,
+        )
+    }
+
+    fn send(&self, event: ContextMenuEvent) {
+        if let Err(err) = self.tx.send(event) {
+            tracing::error!(?err, "cannot send context menu event");
+        }
     }
 }
 
+fn menu_items(model: &MenuModel) -> Vec<ContextMenuItem> {
+    (0..model.count())
+        .filter_map(|index| {
+            match model.type_at(index).as_ref() {
+                sys::cef_menu_item_type_t::MENUITEMTYPE_SEPARATOR => {
+                    Some(ContextMenuItem::Separator)
+                }
+                sys::cef_menu_item_type_t::MENUITEMTYPE_COMMAND
+                | sys::cef_menu_item_type_t::MENUITEMTYPE_CHECK
+                | sys::cef_menu_item_type_t::MENUITEMTYPE_RADIO => Some(ContextMenuItem::Command {
+                    command: MenuCommand::from_id(model.command_id_at(index)),
+                    // labels use `&` as accelerator prefix
+                    label: CefString::from(&model.label_at(index))
+                        .to_string()
+                        .replace('&', ""),
+                    enabled: model.is_enabled_at(index) == 1,
+                }),
+                // submenus are not supported
+                _ => None,
+            }
+        })
+        .collect()
+}
+
 pub(crate) struct ContextMenuHandlerBuilder {
     object: *mut RcImpl<sys::_cef_context_menu_handler_t, Self>,
     context_menu_handler: IcyContextMenuHandler,
@@ -63,15 +320,113 @@ impl ImplContextMenuHandler for ContextMenuHandlerBuilder {
     fn get_raw(&self) -> *mut sys::_cef_context_menu_handler_t {
         self.object.cast()
     }
+
     fn on_before_context_menu(
         &self,
         _browser: Option<&mut Browser>,
         _frame: Option<&mut Frame>,
-        _params: Option<&mut ContextMenuParams>,
+        params: Option<&mut cef::ContextMenuParams>,
         model: Option<&mut MenuModel>,
     ) {
-        if let Some(model) = model {
-            model.clear();
+        let (Some(params), Some(model)) = (params, model) else {
+            return;
+        };
+        let Some(builder) = &self.context_menu_handler.builder else {
+            return;
+        };
+
+        let mut items = menu_items(model);
+        builder(&(&*params).into(), &mut items);
+        model.clear();
+        for item in items {
+            match item {
+                ContextMenuItem::Separator => {
+                    model.add_separator();
+                }
+                ContextMenuItem::Command {
+                    command,
+                    label,
+                    enabled,
+                } => {
+                    let Some(id) = command.id() else {
+                        tracing::warn!(?command, label, "menu command id out of range");
+                        continue;
+                    };
+                    model.add_item(id, Some(&label.as_str().into()));
+                    model.set_enabled(id, enabled as _);
+                }
+            }
+        }
+    }
+
+    fn run_context_menu(
+        &self,
+        browser: Option<&mut Browser>,
+        _frame: Option<&mut Frame>,
+        params: Option<&mut cef::ContextMenuParams>,
+        model: Option<&mut MenuModel>,
+        callback: Option<&mut RunContextMenuCallback>,
+    ) -> ::std::os::raw::c_int {
+        let (Some(browser), Some(params), Some(model), Some(callback)) =
+            (browser, params, model, callback)
+        else {
+            return false as _;
+        };
+        let items = menu_items(model);
+        if items.is_empty() {
+            callback.cancel();
+            return true as _;
+        }
+
+        // a menu still open is replaced by the new one
+        if let Some(pending) = self
+            .context_menu_handler
+            .state
+            .callback
+            .borrow_mut()
+            .replace(callback.clone())
+        {
+            pending.cancel();
+        }
+        self.context_menu_handler
+            .send(ContextMenuEvent::Show(ContextMenu {
+                browser_id: browser.identifier().into(),
+                params: (&*params).into(),
+                items,
+            }));
+        true as _
+    }
+
+    fn on_context_menu_command(
+        &self,
+        browser: Option<&mut Browser>,
+        _frame: Option<&mut Frame>,
+        _params: Option<&mut cef::ContextMenuParams>,
+        command_id: ::std::os::raw::c_int,
+        _event_flags: EventFlags,
+    ) -> ::std::os::raw::c_int {
+        let Some(browser) = browser else {
+            return false as _;
+        };
+        match MenuCommand::from_id(command_id) {
+            MenuCommand::Custom(id) => {
+                self.context_menu_handler.send(ContextMenuEvent::Command {
+                    browser_id: browser.identifier().into(),
+                    id,
+                });
+                true as _
+            }
+            MenuCommand::Builtin(_) => false as _,
         }
     }
+
+    fn on_context_menu_dismissed(&self, browser: Option<&mut Browser>, _frame: Option<&mut Frame>) {
+        let Some(browser) = browser else {
+            return;
+        };
+        self.context_menu_handler.state.callback.borrow_mut().take();
+        self.context_menu_handler.send(ContextMenuEvent::Dismissed {
+            browser_id: browser.identifier().into(),
+        });
+    }
 }
@@ -0,0 +1,235 @@
+use camino::Utf8PathBuf;
+use cef;
+use cef::{
+    DialogHandler, ImplBrowser, ImplDialogHandler, ImplFileDialogCallback, WrapDialogHandler,
+    rc::{Rc, RcImpl},
+    sys, *,
+};
+use std::{cell::RefCell, collections::BTreeMap, ptr::null_mut};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+use crate::BrowserId;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileDialogMode {
+    Open,
+    OpenMultiple,
+    OpenFolder,
+    Save,
+}
+
+impl From<cef::FileDialogMode> for FileDialogMode {
+    fn from(mode: cef::FileDialogMode) -> Self {
+        use cef::sys::cef_file_dialog_mode_t;
+        match mode.as_ref() {
+            cef_file_dialog_mode_t::FILE_DIALOG_OPEN_MULTIPLE => Self::OpenMultiple,
+            cef_file_dialog_mode_t::FILE_DIALOG_OPEN_FOLDER => Self::OpenFolder,
+            cef_file_dialog_mode_t::FILE_DIALOG_SAVE => Self::Save,
+            _ => Self::Open,
+        }
+    }
+}
+
+/// A file chooser opened by `<input type=file>` or `showOpenFilePicker`
+#[derive(Clone, Debug)]
+pub struct FileDialogRequest {
+    pub browser_id: BrowserId,
+    /// Pass back with [`crate::CefMessage::FileDialogResponse`]
+    pub id: u32,
+    pub mode: FileDialogMode,
+    pub title: String,
+    pub default_path: Option<Utf8PathBuf>,
+    /// Mime types(`image/*`) or extensions(`.png`) from the `accept` attribute
+    pub accept_filters: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FileDialogResponse {
+    Selected(Vec<Utf8PathBuf>),
+    Cancelled,
+    /// Forward the request to the application as [`crate::CefAction::FileDialog`]
+    Deferred,
+}
+
+/// Answers file choosers synchronously, e.g. a native picker or a test fixture.
+/// File choosers are deferred to the application if no provider is configured.
+pub trait FileDialogProvider: 'static {
+    fn choose(&self, request: &FileDialogRequest) -> FileDialogResponse;
+}
+
+impl<F> FileDialogProvider for F
+where
+    F: Fn(&FileDialogRequest) -> FileDialogResponse + 'static,
+{
+    fn choose(&self, request: &FileDialogRequest) -> FileDialogResponse {
+        self(request)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct IcyDialogState {
+    next_id: std::rc::Rc<RefCell<u32>>,
+    pending: std::rc::Rc<RefCell<BTreeMap<u32, cef::FileDialogCallback>>>,
+}
+
+impl std::fmt::Debug for IcyDialogState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IcyDialogState")
+            .field("pending", &self.pending.borrow().keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl IcyDialogState {
+    fn register(&self, callback: cef::FileDialogCallback) -> u32 {
+        let mut next_id = self.next_id.borrow_mut();
+        let id = *next_id;
+        *next_id = next_id.wrapping_add(1);
+        self.pending.borrow_mut().insert(id, callback);
+        id
+    }
+
+    /// Complete the file chooser `id`, `None` cancels it
+    pub(crate) fn respond(&self, id: u32, paths: Option<Vec<Utf8PathBuf>>) {
+        if let Some(callback) = self.pending.borrow_mut().remove(&id) {
+            respond(&callback, paths);
+        }
+    }
+}
+
+fn respond(callback: &cef::FileDialogCallback, paths: Option<Vec<Utf8PathBuf>>) {
+    match paths {
+        Some(paths) => {
+            let mut list = CefStringList::new();
+            for path in &paths {
+                list.append(path.as_str());
+            }
+            callback.cont(Some(&mut list));
+        }
+        None => callback.cancel(),
+    }
+}
+
+#[derive(Clone)]
+pub struct IcyDialogHandler {
+    state: IcyDialogState,
+    provider: Option<std::rc::Rc<dyn FileDialogProvider>>,
+    tx: UnboundedSender<FileDialogRequest>,
+}
+
+impl IcyDialogHandler {
+    pub fn new(
+        provider: Option<std::rc::Rc<dyn FileDialogProvider>>,
+    ) -> (Self, IcyDialogState, UnboundedReceiver<FileDialogRequest>) {
+        let state = IcyDialogState::default();
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        (
+            Self {
+                state: state.clone(),
+                provider,
+                tx,
+            },
+            state,
+            rx,
+        )
+    }
+}
+
+pub(crate) struct DialogHandlerBuilder {
+    object: *mut RcImpl<sys::_cef_dialog_handler_t, Self>,
+    dialog_handler: IcyDialogHandler,
+}
+
+impl DialogHandlerBuilder {
+    pub(crate) fn build(dialog_handler: IcyDialogHandler) -> DialogHandler {
+        DialogHandler::new(Self {
+            object: null_mut(),
+            dialog_handler,
+        })
+    }
+}
+
+impl WrapDialogHandler for DialogHandlerBuilder {
+    fn wrap_rc(&mut self, object: *mut RcImpl<sys::_cef_dialog_handler_t, Self>) {
+        self.object = object;
+    }
+}
+
+impl Rc for DialogHandlerBuilder {
+    fn as_base(&self) -> &sys::cef_base_ref_counted_t {
+        unsafe {
+            let base = &*self.object;
+            std::mem::transmute(&base.cef_object)
+        }
+    }
+}
+
+impl Clone for DialogHandlerBuilder {
+    fn clone(&self) -> Self {
+        let object = unsafe {
+            let rc_impl = &mut *self.object;
+            rc_impl.interface.add_ref();
+            rc_impl
+        };
+
+        Self {
+            object,
+            dialog_handler: self.dialog_handler.clone(),
+        }
+    }
+}
+
+impl ImplDialogHandler for DialogHandlerBuilder {
+    fn get_raw(&self) -> *mut sys::_cef_dialog_handler_t {
+        self.object.cast()
+    }
+
+    fn on_file_dialog(
+        &self,
+        browser: Option<&mut Browser>,
+        mode: cef::FileDialogMode,
+        title: Option<&CefString>,
+        default_file_path: Option<&CefString>,
+        accept_filters: Option<&mut CefStringList>,
+        _accept_extensions: Option<&mut CefStringList>,
+        _accept_descriptions: Option<&mut CefStringList>,
+        callback: Option<&mut FileDialogCallback>,
+    ) -> ::std::os::raw::c_int {
+        let (Some(browser), Some(callback)) = (browser, callback) else {
+            return false as _;
+        };
+        let handler = &self.dialog_handler;
+        let mut request = FileDialogRequest {
+            browser_id: browser.identifier().into(),
+            id: 0,
+            mode: mode.into(),
+            title: title.map(|title| title.to_string()).unwrap_or_default(),
+            default_path: default_file_path
+                .map(|path| path.to_string())
+                .filter(|path| !path.is_empty())
+                .map(Into::into),
+            accept_filters: accept_filters
+                .map(|filters| Vec::<String>::from(&*filters))
+                .unwrap_or_default(),
+        };
+
+        match handler
+            .provider
+            .as_ref()
+            .map(|provider| provider.choose(&request))
+            .unwrap_or(FileDialogResponse::Deferred)
+        {
+            FileDialogResponse::Selected(paths) => respond(callback, Some(paths)),
+            FileDialogResponse::Cancelled => respond(callback, None),
+            FileDialogResponse::Deferred => {
+                let id = handler.state.register(callback.clone());
+                request.id = id;
+                if let Err(err) = handler.tx.send(request) {
+                    tracing::error!(?err, "cannot send file dialog request");
+                    handler.state.respond(id, None);
+                }
+            }
+        }
+        true as _
+    }
+}
@@ -1,10 +1,39 @@
 use cef;
 use cef::{rc::*, *};
 use std::{cell::RefCell, ptr::null_mut};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+use crate::BrowserId;
+
+#[derive(Clone, Debug)]
+pub enum DisplayEvent {
+    TitleChanged {
+        browser_id: BrowserId,
+        title: String,
+    },
+    AddressChanged {
+        browser_id: BrowserId,
+        is_main_frame: bool,
+        url: String,
+    },
+    FaviconUrlChanged {
+        browser_id: BrowserId,
+        icon_urls: Vec<String>,
+    },
+    StatusMessage {
+        browser_id: BrowserId,
+        message: Option<String>,
+    },
+    LoadingProgressChanged {
+        browser_id: BrowserId,
+        progress: f64,
+    },
+}
 
 #[derive(Clone)]
 pub struct IcyDisplayHandler {
     state: IcyDisplayState,
+    tx: UnboundedSender<DisplayEvent>,
 }
 
 #[derive(Clone, Debug)]
@@ -13,17 +42,26 @@ pub struct IcyDisplayState {
 }
 
 impl IcyDisplayHandler {
-    pub fn new() -> (Self, IcyDisplayState) {
+    pub fn new() -> (Self, IcyDisplayState, UnboundedReceiver<DisplayEvent>) {
         let state = IcyDisplayState {
             cursor_type: std::rc::Rc::new(RefCell::new(CursorType::default())),
         };
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
         (
             Self {
                 state: state.clone(),
+                tx,
             },
             state,
+            rx,
         )
     }
+
+    fn send(&self, event: DisplayEvent) {
+        if let Err(err) = self.tx.send(event) {
+            tracing::error!(?err, "cannot send display event");
+        }
+    }
 }
 
 pub(crate) struct DisplayHandlerBuilder {
@@ -87,4 +125,69 @@ impl ImplDisplayHandler for DisplayHandlerBuilder {
         *self.display_handler.state.cursor_type.borrow_mut() = type_;
         return true as _;
     }
+
+    fn on_title_change(&self, browser: Option<&mut Browser>, title: Option<&CefString>) {
+        let Some(browser) = browser else {
+            return;
+        };
+        self.display_handler.send(DisplayEvent::TitleChanged {
+            browser_id: browser.identifier().into(),
+            title: title.map(ToString::to_string).unwrap_or_default(),
+        });
+    }
+
+    fn on_address_change(
+        &self,
+        browser: Option<&mut Browser>,
+        frame: Option<&mut Frame>,
+        url: Option<&CefString>,
+    ) {
+        let Some(browser) = browser else {
+            return;
+        };
+        let Some(url) = url else {
+            return;
+        };
+        self.display_handler.send(DisplayEvent::AddressChanged {
+            browser_id: browser.identifier().into(),
+            is_main_frame: frame.is_some_and(|frame| frame.is_main() == 1),
+            url: url.to_string(),
+        });
+    }
+
+    fn on_favicon_urlchange(
+        &self,
+        browser: Option<&mut Browser>,
+        icon_urls: Option<&mut CefStringList>,
+    ) {
+        let Some(browser) = browser else {
+            return;
+        };
+        self.display_handler.send(DisplayEvent::FaviconUrlChanged {
+            browser_id: browser.identifier().into(),
+            icon_urls: icon_urls
+                .map(|urls| Vec::<String>::from(&*urls))
+                .unwrap_or_default(),
+        });
+    }
+
+    fn on_status_message(&self, browser: Option<&mut Browser>, value: Option<&CefString>) {
+        let Some(browser) = browser else {
+            return;
+        };
+        self.display_handler.send(DisplayEvent::StatusMessage {
+            browser_id: browser.identifier().into(),
+            message: value.map(ToString::to_string).filter(|s| !s.is_empty()),
+        });
+    }
+
+    fn on_loading_progress_change(&self, browser: Option<&mut Browser>, progress: f64) {
+        let Some(browser) = browser else {
+            return;
+        };
+        self.display_handler.send(DisplayEvent::LoadingProgressChanged {
+            browser_id: browser.identifier().into(),
+            progress,
+        });
+    }
 }
@@ -0,0 +1,316 @@
+use camino::{Utf8Path, Utf8PathBuf};
+use cef;
+use cef::{
+    DownloadHandler, ImplBrowser, ImplDownloadHandler, ImplDownloadItem, WrapDownloadHandler,
+    rc::{Rc, RcImpl},
+    sys, *,
+};
+use std::{cell::RefCell, collections::BTreeMap, ptr::null_mut};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+use crate::BrowserId;
+
+/// What happens to a download once the page starts it
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum DownloadPolicy {
+    /// Wait for [`crate::CefMessage::DownloadCommand`] with [`DownloadCommand::SaveTo`]
+    #[default]
+    Ask,
+    /// Save with the suggested name into this directory, without overwriting
+    SaveTo(Utf8PathBuf),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DownloadCommand {
+    /// Full path of the target file, only valid for a download that has not begun yet
+    SaveTo(Utf8PathBuf),
+    Cancel,
+    Pause,
+    /// Continue a paused or interrupted download
+    Resume,
+}
+
+#[derive(Clone, Debug)]
+pub enum DownloadEvent {
+    Started {
+        browser_id: BrowserId,
+        id: u32,
+        url: String,
+        suggested_name: String,
+        mime_type: String,
+        total_bytes: Option<u64>,
+    },
+    Progress {
+        browser_id: BrowserId,
+        id: u32,
+        received_bytes: u64,
+        total_bytes: Option<u64>,
+        /// bytes per second
+        speed: u64,
+    },
+    Completed {
+        browser_id: BrowserId,
+        id: u32,
+        path: Utf8PathBuf,
+    },
+    Cancelled {
+        browser_id: BrowserId,
+        id: u32,
+    },
+    Interrupted {
+        browser_id: BrowserId,
+        id: u32,
+        /// `cef_download_interrupt_reason_t`
+        reason: i32,
+    },
+}
+
+#[derive(Clone, Default)]
+pub struct IcyDownloadState {
+    /// downloads waiting for a target path
+    pending: std::rc::Rc<RefCell<BTreeMap<u32, cef::BeforeDownloadCallback>>>,
+    /// downloads in progress
+    items: std::rc::Rc<RefCell<BTreeMap<u32, cef::DownloadItemCallback>>>,
+}
+
+impl std::fmt::Debug for IcyDownloadState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IcyDownloadState")
+            .field("pending", &self.pending.borrow().keys().collect::<Vec<_>>())
+            .field("items", &self.items.borrow().keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl IcyDownloadState {
+    pub(crate) fn run(&self, id: u32, command: DownloadCommand) {
+        match command {
+            DownloadCommand::SaveTo(path) => {
+                if let Some(callback) = self.pending.borrow_mut().remove(&id) {
+                    callback.cont(Some(&path.as_str().into()), false as _);
+                }
+            }
+            DownloadCommand::Cancel => {
+                // a dropped before-download callback cancels the download
+                self.pending.borrow_mut().remove(&id);
+                if let Some(callback) = self.items.borrow().get(&id) {
+                    callback.cancel();
+                }
+            }
+            DownloadCommand::Pause => {
+                if let Some(callback) = self.items.borrow().get(&id) {
+                    callback.pause();
+                }
+            }
+            DownloadCommand::Resume => {
+                if let Some(callback) = self.items.borrow().get(&id) {
+                    callback.resume();
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct IcyDownloadHandler {
+    state: IcyDownloadState,
+    policy: DownloadPolicy,
+    tx: UnboundedSender<DownloadEvent>,
+}
+
+impl IcyDownloadHandler {
+    pub fn new(
+        policy: DownloadPolicy,
+    ) -> (Self, IcyDownloadState, UnboundedReceiver<DownloadEvent>) {
+        let state = IcyDownloadState::default();
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        (
+            Self {
+                state: state.clone(),
+                policy,
+                tx,
+            },
+            state,
+            rx,
+        )
+    }
+
+    fn send(&self, event: DownloadEvent) {
+        if let Err(err) = self.tx.send(event) {
+            tracing::error!(?err, "cannot send download event");
+        }
+    }
+}
+
+/// `dir/name`, or `dir/name (n).ext` if that is taken
+fn unique_path(dir: &Utf8Path, name: &str) -> Utf8PathBuf {
+    let name = Utf8Path::new(name)
+        .file_name()
+        .filter(|name| !name.is_empty())
+        .unwrap_or("download");
+    let path = dir.join(name);
+    if !path.exists() {
+        return path;
+    }
+    let stem = path.file_stem().unwrap_or(name).to_string();
+    let extension = path.extension().map(|ext| format!(".{ext}"));
+    (1..)
+        .map(|n| {
+            dir.join(format!(
+                "{stem} ({n}){}",
+                extension.as_deref().unwrap_or("")
+            ))
+        })
+        .find(|path| !path.exists())
+        .unwrap_or(path)
+}
+
+fn total_bytes(item: &DownloadItem) -> Option<u64> {
+    Some(item.total_bytes())
+        .filter(|total| *total > 0)
+        .map(|total| total as _)
+}
+
+pub(crate) struct DownloadHandlerBuilder {
+    object: *mut RcImpl<sys::_cef_download_handler_t, Self>,
+    download_handler: IcyDownloadHandler,
+}
+
+impl DownloadHandlerBuilder {
+    pub(crate) fn build(download_handler: IcyDownloadHandler) -> DownloadHandler {
+        DownloadHandler::new(Self {
+            object: null_mut(),
+            download_handler,
+        })
+    }
+}
+
+impl WrapDownloadHandler for DownloadHandlerBuilder {
+    fn wrap_rc(&mut self, object: *mut RcImpl<sys::_cef_download_handler_t, Self>) {
+        self.object = object;
+    }
+}
+
+impl Rc for DownloadHandlerBuilder {
+    fn as_base(&self) -> &sys::cef_base_ref_counted_t {
+        unsafe {
+            let base = &*self.object;
+            std::mem::transmute(&base.cef_object)
+        }
+    }
+}
+
+impl Clone for DownloadHandlerBuilder {
+    fn clone(&self) -> Self {
+        let object = unsafe {
+            let rc_impl = &mut *self.object;
+            rc_impl.interface.add_ref();
+            rc_impl
+        };
+
+        Self {
+            object,
+            download_handler: self.download_handler.clone(),
+        }
+    }
+}
+
+impl ImplDownloadHandler for DownloadHandlerBuilder {
+    fn get_raw(&self) -> *mut sys::_cef_download_handler_t {
+        self.object.cast()
+    }
+
+    fn on_before_download(
+        &self,
+        browser: Option<&mut Browser>,
+        download_item: Option<&mut DownloadItem>,
+        suggested_name: Option<&CefString>,
+        callback: Option<&mut BeforeDownloadCallback>,
+    ) -> ::std::os::raw::c_int {
+        let (Some(browser), Some(item), Some(callback)) = (browser, download_item, callback) else {
+            return false as _;
+        };
+        let id = item.id();
+        let suggested_name = suggested_name
+            .map(|name| name.to_string())
+            .unwrap_or_default();
+        match &self.download_handler.policy {
+            DownloadPolicy::SaveTo(dir) => {
+                let path = unique_path(dir, &suggested_name);
+                callback.cont(Some(&path.as_str().into()), false as _);
+            }
+            DownloadPolicy::Ask => {
+                self.download_handler
+                    .state
+                    .pending
+                    .borrow_mut()
+                    .insert(id, callback.clone());
+            }
+        }
+        self.download_handler.send(DownloadEvent::Started {
+            browser_id: browser.identifier().into(),
+            id,
+            url: CefString::from(&item.url()).to_string(),
+            suggested_name,
+            mime_type: CefString::from(&item.mime_type()).to_string(),
+            total_bytes: total_bytes(item),
+        });
+        true as _
+    }
+
+    fn on_download_updated(
+        &self,
+        browser: Option<&mut Browser>,
+        download_item: Option<&mut DownloadItem>,
+        callback: Option<&mut DownloadItemCallback>,
+    ) {
+        let (Some(browser), Some(item)) = (browser, download_item) else {
+            return;
+        };
+        if item.is_valid() != 1 {
+            return;
+        }
+        let browser_id: BrowserId = browser.identifier().into();
+        let id = item.id();
+        let state = &self.download_handler.state;
+
+        let event = if item.is_complete() == 1 {
+            DownloadEvent::Completed {
+                browser_id,
+                id,
+                path: CefString::from(&item.full_path()).to_string().into(),
+            }
+        } else if item.is_canceled() == 1 {
+            DownloadEvent::Cancelled { browser_id, id }
+        } else if item.is_interrupted() == 1 {
+            // keep the callback, an interrupted download can be resumed
+            if let Some(callback) = callback {
+                state.items.borrow_mut().insert(id, callback.clone());
+            }
+            DownloadEvent::Interrupted {
+                browser_id,
+                id,
+                reason: *item.interrupt_reason().as_ref() as _,
+            }
+        } else {
+            if let Some(callback) = callback {
+                state.items.borrow_mut().insert(id, callback.clone());
+            }
+            DownloadEvent::Progress {
+                browser_id,
+                id,
+                received_bytes: item.received_bytes().max(0) as _,
+                total_bytes: total_bytes(item),
+                speed: item.current_speed().max(0) as _,
+            }
+        };
+        if matches!(
+            event,
+            DownloadEvent::Completed { .. } | DownloadEvent::Cancelled { .. }
+        ) {
+            state.items.borrow_mut().remove(&id);
+            state.pending.borrow_mut().remove(&id);
+        }
+        self.download_handler.send(event);
+    }
+}
@@ -0,0 +1,121 @@
+use cef;
+use cef::{
+    FindHandler, ImplBrowser, ImplFindHandler, WrapFindHandler,
+    rc::{Rc, RcImpl},
+    sys, *,
+};
+use std::ptr::null_mut;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+use crate::BrowserId;
+
+/// Progress of a find-in-page search, sent several times per search
+#[derive(Clone, Debug)]
+pub struct FindResult {
+    pub browser_id: BrowserId,
+    /// Identifies the search, increases with every new search text
+    pub identifier: i32,
+    /// Number of matches found so far
+    pub count: u32,
+    /// 1-based index of the highlighted match, 0 if there is none
+    pub active_match_ordinal: u32,
+    /// Selection of the highlighted match in view coordinates
+    pub selection_rect: iced::Rectangle,
+    /// No more results will follow for this search
+    pub final_update: bool,
+}
+
+#[derive(Clone)]
+pub struct IcyFindHandler {
+    tx: UnboundedSender<FindResult>,
+}
+
+impl IcyFindHandler {
+    pub fn new() -> (Self, UnboundedReceiver<FindResult>) {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        (Self { tx }, rx)
+    }
+}
+
+pub(crate) struct FindHandlerBuilder {
+    object: *mut RcImpl<sys::_cef_find_handler_t, Self>,
+    find_handler: IcyFindHandler,
+}
+
+impl FindHandlerBuilder {
+    pub(crate) fn build(find_handler: IcyFindHandler) -> FindHandler {
+        FindHandler::new(Self {
+            object: null_mut(),
+            find_handler,
+        })
+    }
+}
+
+impl WrapFindHandler for FindHandlerBuilder {
+    fn wrap_rc(&mut self, object: *mut RcImpl<sys::_cef_find_handler_t, Self>) {
+        self.object = object;
+    }
+}
+
+impl Rc for FindHandlerBuilder {
+    fn as_base(&self) -> &sys::cef_base_ref_counted_t {
+        unsafe {
+            let base = &*self.object;
+            std::mem::transmute(&base.cef_object)
+        }
+    }
+}
+
+impl Clone for FindHandlerBuilder {
+    fn clone(&self) -> Self {
+        let object = unsafe {
+            let rc_impl = &mut *self.object;
+            rc_impl.interface.add_ref();
+            rc_impl
+        };
+
+        Self {
+            object,
+            find_handler: self.find_handler.clone(),
+        }
+    }
+}
+
+impl ImplFindHandler for FindHandlerBuilder {
+    fn get_raw(&self) -> *mut sys::_cef_find_handler_t {
+        self.object.cast()
+    }
+
+    fn on_find_result(
+        &self,
+        browser: Option<&mut Browser>,
+        identifier: ::std::os::raw::c_int,
+        count: ::std::os::raw::c_int,
+        selection_rect: Option<&Rect>,
+        active_match_ordinal: ::std::os::raw::c_int,
+        final_update: ::std::os::raw::c_int,
+    ) {
+        let Some(browser) = browser else {
+            return;
+        };
+        let selection_rect = selection_rect
+            .map(|rect| iced::Rectangle {
+                x: rect.x as f32,
+                y: rect.y as f32,
+                width: rect.width as f32,
+                height: rect.height as f32,
+            })
+            .unwrap_or_default();
+        let result = FindResult {
+            browser_id: browser.identifier().into(),
+            identifier,
+            count: count.max(0) as _,
+            active_match_ordinal: active_match_ordinal.max(0) as _,
+            selection_rect,
+            final_update: final_update == 1,
+        };
+        if let Err(err) = self.find_handler.tx.send(result) {
+            tracing::error!(?err, "cannot send find result");
+        }
+    }
+}
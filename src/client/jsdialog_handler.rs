@@ -0,0 +1,245 @@
+use cef;
+use cef::{
+    ImplBrowser, ImplJsdialogCallback, ImplJsdialogHandler, JsdialogHandler, WrapJsdialogHandler,
+    rc::{Rc, RcImpl},
+    sys, *,
+};
+use std::{cell::RefCell, collections::BTreeMap, ptr::null_mut};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+use crate::BrowserId;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JsDialogKind {
+    Alert,
+    Confirm,
+    Prompt {
+        default_text: String,
+    },
+    /// `onbeforeunload`, accepting leaves the page
+    BeforeUnload {
+        is_reload: bool,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct JsDialog {
+    pub browser_id: BrowserId,
+    /// Pass back with [`crate::CefMessage::JsDialogResponse`]
+    pub id: u32,
+    pub kind: JsDialogKind,
+    /// Empty for `onbeforeunload`
+    pub origin_url: String,
+    pub message: String,
+}
+
+#[derive(Clone, Debug)]
+pub enum JsDialogEvent {
+    Open(JsDialog),
+    /// Pending dialogs were dismissed by navigation or the page
+    Reset {
+        browser_id: BrowserId,
+    },
+}
+
+#[derive(Clone, Default)]
+pub struct IcyJsDialogState {
+    next_id: std::rc::Rc<RefCell<u32>>,
+    pending: std::rc::Rc<RefCell<BTreeMap<u32, cef::JsdialogCallback>>>,
+}
+
+impl std::fmt::Debug for IcyJsDialogState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IcyJsDialogState")
+            .field("pending", &self.pending.borrow().keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl IcyJsDialogState {
+    fn register(&self, callback: cef::JsdialogCallback) -> u32 {
+        let mut next_id = self.next_id.borrow_mut();
+        let id = *next_id;
+        *next_id = next_id.wrapping_add(1);
+        self.pending.borrow_mut().insert(id, callback);
+        id
+    }
+
+    /// Close dialog `id`, `input` is only used by prompts
+    pub(crate) fn respond(&self, id: u32, accepted: bool, input: Option<String>) {
+        if let Some(callback) = self.pending.borrow_mut().remove(&id) {
+            callback.cont(
+                accepted as _,
+                input.as_deref().map(CefString::from).as_ref(),
+            );
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct IcyJsDialogHandler {
+    enabled: bool,
+    state: IcyJsDialogState,
+    tx: UnboundedSender<JsDialogEvent>,
+}
+
+impl IcyJsDialogHandler {
+    pub fn new(enabled: bool) -> (Self, IcyJsDialogState, UnboundedReceiver<JsDialogEvent>) {
+        let state = IcyJsDialogState::default();
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        (
+            Self {
+                enabled,
+                state: state.clone(),
+                tx,
+            },
+            state,
+            rx,
+        )
+    }
+
+    fn open(
+        &self,
+        browser: &Browser,
+        kind: JsDialogKind,
+        origin_url: Option<&CefString>,
+        message: Option<&CefString>,
+        callback: &JsdialogCallback,
+    ) {
+        let dialog = JsDialog {
+            browser_id: browser.identifier().into(),
+            id: self.state.register(callback.clone()),
+            kind,
+            origin_url: origin_url.map(|url| url.to_string()).unwrap_or_default(),
+            message: message
+                .map(|message| message.to_string())
+                .unwrap_or_default(),
+        };
+        if let Err(err) = self.tx.send(JsDialogEvent::Open(dialog)) {
+            tracing::error!(?err, "cannot send js dialog event");
+        }
+    }
+}
+
+pub(crate) struct JsDialogHandlerBuilder {
+    object: *mut RcImpl<sys::_cef_jsdialog_handler_t, Self>,
+    jsdialog_handler: IcyJsDialogHandler,
+}
+
+impl JsDialogHandlerBuilder {
+    pub(crate) fn build(jsdialog_handler: IcyJsDialogHandler) -> JsdialogHandler {
+        JsdialogHandler::new(Self {
+            object: null_mut(),
+            jsdialog_handler,
+        })
+    }
+}
+
+impl WrapJsdialogHandler for JsDialogHandlerBuilder {
+    fn wrap_rc(&mut self, object: *mut RcImpl<sys::_cef_jsdialog_handler_t, Self>) {
+        self.object = object;
+    }
+}
+
+impl Rc for JsDialogHandlerBuilder {
+    fn as_base(&self) -> &sys::cef_base_ref_counted_t {
+        unsafe {
+            let base = &*self.object;
+            std::mem::transmute(&base.cef_object)
+        }
+    }
+}
+
+impl Clone for JsDialogHandlerBuilder {
+    fn clone(&self) -> Self {
+        let object = unsafe {
+            let rc_impl = &mut *self.object;
+            rc_impl.interface.add_ref();
+            rc_impl
+        };
+
+        Self {
+            object,
+            jsdialog_handler: self.jsdialog_handler.clone(),
+        }
+    }
+}
+
+impl ImplJsdialogHandler for JsDialogHandlerBuilder {
+    fn get_raw(&self) -> *mut sys::_cef_jsdialog_handler_t {
+        self.object.cast()
+    }
+
+    fn on_jsdialog(
+        &self,
+        browser: Option<&mut Browser>,
+        origin_url: Option<&CefString>,
+        dialog_type: JsdialogType,
+        message_text: Option<&CefString>,
+        default_prompt_text: Option<&CefString>,
+        callback: Option<&mut JsdialogCallback>,
+        suppress_message: Option<&mut ::std::os::raw::c_int>,
+    ) -> ::std::os::raw::c_int {
+        if !self.jsdialog_handler.enabled {
+            // nothing would show the dialog, suppress it like a blocked popup
+            if let Some(suppress_message) = suppress_message {
+                *suppress_message = true as _;
+            }
+            return false as _;
+        }
+        let (Some(browser), Some(callback)) = (browser, callback) else {
+            return false as _;
+        };
+        let kind = match dialog_type.as_ref() {
+            sys::cef_jsdialog_type_t::JSDIALOGTYPE_CONFIRM => JsDialogKind::Confirm,
+            sys::cef_jsdialog_type_t::JSDIALOGTYPE_PROMPT => JsDialogKind::Prompt {
+                default_text: default_prompt_text
+                    .map(|text| text.to_string())
+                    .unwrap_or_default(),
+            },
+            _ => JsDialogKind::Alert,
+        };
+        self.jsdialog_handler
+            .open(browser, kind, origin_url, message_text, callback);
+        true as _
+    }
+
+    fn on_before_unload_dialog(
+        &self,
+        browser: Option<&mut Browser>,
+        message_text: Option<&CefString>,
+        is_reload: ::std::os::raw::c_int,
+        callback: Option<&mut JsdialogCallback>,
+    ) -> ::std::os::raw::c_int {
+        let (Some(browser), Some(callback)) = (browser, callback) else {
+            return false as _;
+        };
+        if !self.jsdialog_handler.enabled {
+            // windowless browsers have no default dialog, leave the page
+            callback.cont(true as _, None);
+            return true as _;
+        }
+        self.jsdialog_handler.open(
+            browser,
+            JsDialogKind::BeforeUnload {
+                is_reload: is_reload == 1,
+            },
+            None,
+            message_text,
+            callback,
+        );
+        true as _
+    }
+
+    fn on_reset_dialog_state(&self, browser: Option<&mut Browser>) {
+        let Some(browser) = browser else {
+            return;
+        };
+        self.jsdialog_handler.state.pending.borrow_mut().clear();
+        if let Err(err) = self.jsdialog_handler.tx.send(JsDialogEvent::Reset {
+            browser_id: browser.identifier().into(),
+        }) {
+            tracing::error!(?err, "cannot send js dialog event");
+        }
+    }
+}
@@ -2,6 +2,19 @@ use cef;
 use cef::{rc::*, *};
 use std::{cell::RefCell, ffi::*, ptr::null_mut};
 
+/// Ctrl, Command on macOS, without Alt or AltGr, those combinations type characters
+/// on many layouts
+fn is_shortcut(modifiers: u32) -> bool {
+    use cef::sys::cef_event_flags_t;
+    #[cfg(target_os = "macos")]
+    let shortcut = cef_event_flags_t::EVENTFLAG_COMMAND_DOWN.0;
+    #[cfg(not(target_os = "macos"))]
+    let shortcut = cef_event_flags_t::EVENTFLAG_CONTROL_DOWN.0;
+    let typing =
+        cef_event_flags_t::EVENTFLAG_ALT_DOWN.0 | cef_event_flags_t::EVENTFLAG_ALTGR_DOWN.0;
+    modifiers & shortcut != 0 && modifiers & typing == 0
+}
+
 #[derive(Clone)]
 pub struct IcyKeyboardHandler {
     state: IcyKeyboardState,
@@ -98,22 +111,16 @@ impl ImplKeyboardHandler for KeyboardHandlerBuilder {
             .state
             .focus_on_editable_field
             .borrow_mut() = event.focus_on_editable_field == 1;
-        #[cfg(target_os = "macos")]
-        let ctrl = cef::sys::cef_event_flags_t(event.modifiers)
-            & cef::sys::cef_event_flags_t::EVENTFLAG_COMMAND_DOWN
-            == cef::sys::cef_event_flags_t::EVENTFLAG_COMMAND_DOWN;
-
-        #[cfg(not(target_os = "macos"))]
-        let ctrl = cef::sys::cef_event_flags_t(event.modifiers)
-            & cef::sys::cef_event_flags_t::EVENTFLAG_CONTROL_DOWN
-            == cef::sys::cef_event_flags_t::EVENTFLAG_CONTROL_DOWN;
+        let ctrl = is_shortcut(event.modifiers);
         if event.type_ == cef::sys::cef_key_event_type_t::KEYEVENT_RAWKEYDOWN.into() {
             match event.windows_key_code {
-                97 | 122 | 120 | 99 | 118 | 121 if ctrl => {
+                // A, Z, X, C, V, Y
+                0x41 | 0x5A | 0x58 | 0x43 | 0x56 | 0x59 if ctrl => {
                     is_keyboard_shortcut.map(|v| *v = true as _);
                     return false as _;
                 }
-                123 => {
+                // F12
+                0x7B => {
                     is_keyboard_shortcut.map(|v| *v = true as _);
                     return false as _;
                 }
@@ -139,30 +146,25 @@ impl ImplKeyboardHandler for KeyboardHandlerBuilder {
         let Some(event) = event else {
             return false as _;
         };
-        #[cfg(target_os = "macos")]
-        let ctrl = cef::sys::cef_event_flags_t(event.modifiers)
-            & cef::sys::cef_event_flags_t::EVENTFLAG_COMMAND_DOWN
-            == cef::sys::cef_event_flags_t::EVENTFLAG_COMMAND_DOWN;
-
-        #[cfg(not(target_os = "macos"))]
-        let ctrl =
-            event.modifiers & (cef::sys::cef_event_flags_t::EVENTFLAG_CONTROL_DOWN as u32) != 0;
+        let ctrl = is_shortcut(event.modifiers);
+        let shift = event.modifiers & cef::sys::cef_event_flags_t::EVENTFLAG_SHIFT_DOWN.0 != 0;
         let keydown = event.type_ == cef::sys::cef_key_event_type_t::KEYEVENT_RAWKEYDOWN.into();
         if keydown {
             if ctrl {
                 match event.windows_key_code {
-                    97 => frame.select_all(), // A
-                    122 => frame.undo(),      // Z
-                    120 => frame.cut(),       // X
-                    99 => frame.copy(),       // C
-                    118 => frame.paste(),     // V
-                    121 => frame.redo(),      // Y
+                    0x41 => frame.select_all(),    // A
+                    0x5A if shift => frame.redo(), // Shift+Z
+                    0x5A => frame.undo(),          // Z
+                    0x58 => frame.cut(),           // X
+                    0x43 => frame.copy(),          // C
+                    0x56 => frame.paste(),         // V
+                    0x59 => frame.redo(),          // Y
 
                     _ => return false as _,
                 }
             } else {
                 match event.windows_key_code {
-                    123 => {
+                    0x7B => {
                         // F12
                         if let Some(host) = browser.host() {
                             if host.has_dev_tools() == 1 {
@@ -1,29 +1,173 @@
+use atomic_take::AtomicTake;
 use cef;
-use cef::{ImplBrowser, ImplLifeSpanHandler, LifeSpanHandler, WrapLifeSpanHandler, rc::*, sys};
-use std::ptr::null_mut;
-use tokio::sync::mpsc::{Receiver, Sender};
+use cef::{
+    ImplBrowser, ImplFrame, ImplLifeSpanHandler, LifeSpanHandler, WrapLifeSpanHandler, rc::*, sys,
+};
+use std::{cell::RefCell, collections::BTreeMap, ptr::null_mut, sync::Arc};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
 use crate::{
     BrowserId,
-    instance::{LaunchId, new_webview, remove_webview},
+    client::{ClientBuilder, ClientEventSubscriber, IcyClient, WebviewOptions},
+    instance::{LaunchId, WEBVIEW_STATES, abort_browser, new_browser, new_webview, remove_webview},
 };
 
 #[derive(Clone, Debug)]
 pub enum LifeSpanEvent {
-    Created { browser_id: BrowserId },
-    Closed { browser_id: BrowserId },
+    Created {
+        browser_id: BrowserId,
+    },
+    Closed {
+        browser_id: BrowserId,
+    },
+    /// A popup was denied or redirected into the opener
+    PopupRequested {
+        request: PopupRequest,
+    },
+    /// A popup browser is being created, pass it to [`crate::CefComponent::from_popup`]
+    PopupOpened {
+        request: PopupRequest,
+        popup: PopupWebview,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowDisposition {
+    Unknown,
+    CurrentTab,
+    SingletonTab,
+    NewForegroundTab,
+    NewBackgroundTab,
+    NewPopup,
+    NewWindow,
+    SaveToDisk,
+    OffTheRecord,
+    IgnoreAction,
+    NewPictureInPicture,
+}
+
+impl From<cef::WindowOpenDisposition> for WindowDisposition {
+    fn from(disposition: cef::WindowOpenDisposition) -> Self {
+        use cef::sys::cef_window_open_disposition_t;
+        match disposition.as_ref() {
+            cef_window_open_disposition_t::CEF_WOD_CURRENT_TAB => Self::CurrentTab,
+            cef_window_open_disposition_t::CEF_WOD_SINGLETON_TAB => Self::SingletonTab,
+            cef_window_open_disposition_t::CEF_WOD_NEW_FOREGROUND_TAB => Self::NewForegroundTab,
+            cef_window_open_disposition_t::CEF_WOD_NEW_BACKGROUND_TAB => Self::NewBackgroundTab,
+            cef_window_open_disposition_t::CEF_WOD_NEW_POPUP => Self::NewPopup,
+            cef_window_open_disposition_t::CEF_WOD_NEW_WINDOW => Self::NewWindow,
+            cef_window_open_disposition_t::CEF_WOD_SAVE_TO_DISK => Self::SaveToDisk,
+            cef_window_open_disposition_t::CEF_WOD_OFF_THE_RECORD => Self::OffTheRecord,
+            cef_window_open_disposition_t::CEF_WOD_IGNORE_ACTION => Self::IgnoreAction,
+            cef_window_open_disposition_t::CEF_WOD_NEW_PICTURE_IN_PICTURE => {
+                Self::NewPictureInPicture
+            }
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// `window.open` features, in css pixels
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PopupFeatures {
+    pub x: Option<i32>,
+    pub y: Option<i32>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub is_popup: bool,
+}
+
+impl From<&cef::PopupFeatures> for PopupFeatures {
+    fn from(features: &cef::PopupFeatures) -> Self {
+        Self {
+            x: (features.x_set == 1).then_some(features.x),
+            y: (features.y_set == 1).then_some(features.y),
+            width: (features.width_set == 1).then_some(features.width),
+            height: (features.height_set == 1).then_some(features.height),
+            is_popup: features.is_popup == 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PopupRequest {
+    pub opener: BrowserId,
+    pub target_url: String,
+    pub target_frame_name: String,
+    pub disposition: WindowDisposition,
+    pub user_gesture: bool,
+    pub features: PopupFeatures,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PopupAction {
+    #[default]
+    Deny,
+    /// Create a new webview, `window.opener` stays connected
+    Open,
+    /// Load the target url in the opener instead
+    NavigateCurrent,
+}
+
+/// Decides synchronously what happens to a popup, runs on the cef ui thread
+pub type PopupPolicy = std::rc::Rc<dyn Fn(&PopupRequest) -> PopupAction>;
+
+/// A popup browser waiting for its [`crate::CefComponent`]
+#[derive(Clone)]
+pub struct PopupWebview {
+    pub(crate) opener: BrowserId,
+    pub(crate) subscribers: Arc<AtomicTake<ClientEventSubscriber>>,
+}
+
+impl std::fmt::Debug for PopupWebview {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PopupWebview")
+            .field("opener", &self.opener)
+            .finish()
+    }
+}
+
+impl PopupWebview {
+    pub fn opener(&self) -> BrowserId {
+        self.opener
+    }
 }
 
 #[derive(Clone)]
 pub struct IcyLifeSpanHandler {
-    tx: Sender<LifeSpanEvent>,
+    tx: UnboundedSender<LifeSpanEvent>,
     launch_id: LaunchId,
+    options: WebviewOptions,
+    /// (opener, popup id) -> launch id of popups not created yet, shared with the popups
+    pending_popups: std::rc::Rc<RefCell<BTreeMap<(BrowserId, i32), LaunchId>>>,
 }
 
 impl IcyLifeSpanHandler {
-    pub fn new(launch_id: LaunchId) -> (Self, Receiver<LifeSpanEvent>) {
-        let (tx, rx) = tokio::sync::mpsc::channel(2);
-        (Self { tx, launch_id }, rx)
+    pub fn new(
+        launch_id: LaunchId,
+        options: WebviewOptions,
+    ) -> (Self, UnboundedReceiver<LifeSpanEvent>) {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        (
+            Self {
+                tx,
+                launch_id,
+                options,
+                pending_popups: Default::default(),
+            },
+            rx,
+        )
+    }
+
+    fn send(&self, event: LifeSpanEvent) {
+        if let Err(err) = self.tx.send(event) {
+            tracing::warn!(?err, "cannot send life span event");
+        }
+    }
+
+    /// Let a popup of `opener` remove itself from the pending popups once created
+    pub(crate) fn opened_by(&mut self, opener: &Self) {
+        self.pending_popups = opener.pending_popups.clone();
     }
 }
 
@@ -80,10 +224,15 @@ impl ImplLifeSpanHandler for LifeSpanHandlerBuilder {
         let Some(browser) = browser else {
             return;
         };
+        let launch_id = self.handler.launch_id;
+        self.handler
+            .pending_popups
+            .borrow_mut()
+            .retain(|_, pending| *pending != launch_id);
         if self
             .handler
             .tx
-            .try_send(LifeSpanEvent::Created {
+            .send(LifeSpanEvent::Created {
                 browser_id: browser.identifier().into(),
             })
             .inspect_err(|err| {
@@ -91,7 +240,7 @@ impl ImplLifeSpanHandler for LifeSpanHandlerBuilder {
             })
             .is_ok()
         {
-            new_webview(self.handler.launch_id, browser.identifier().into());
+            new_webview(launch_id, browser.identifier().into());
         }
     }
 
@@ -101,29 +250,135 @@ impl ImplLifeSpanHandler for LifeSpanHandlerBuilder {
         };
 
         remove_webview(browser.identifier().into());
-        if let Err(err) = self.handler.tx.try_send(LifeSpanEvent::Closed {
+        self.handler.send(LifeSpanEvent::Closed {
             browser_id: browser.identifier().into(),
-        }) {
-            tracing::warn!(?err, "cannot send life span event");
-        }
+        });
     }
 
     fn on_before_popup(
         &self,
-        _browser: Option<&mut cef::Browser>,
+        browser: Option<&mut cef::Browser>,
         _frame: Option<&mut cef::Frame>,
-        _popup_id: ::std::os::raw::c_int,
-        _target_url: Option<&cef::CefString>,
-        _target_frame_name: Option<&cef::CefString>,
-        _target_disposition: cef::WindowOpenDisposition,
-        _user_gesture: ::std::os::raw::c_int,
-        _popup_features: Option<&cef::PopupFeatures>,
-        _window_info: Option<&mut cef::WindowInfo>,
-        _client: Option<&mut Option<cef::Client>>,
+        popup_id: ::std::os::raw::c_int,
+        target_url: Option<&cef::CefString>,
+        target_frame_name: Option<&cef::CefString>,
+        target_disposition: cef::WindowOpenDisposition,
+        user_gesture: ::std::os::raw::c_int,
+        popup_features: Option<&cef::PopupFeatures>,
+        window_info: Option<&mut cef::WindowInfo>,
+        client: Option<&mut Option<cef::Client>>,
         _settings: Option<&mut cef::BrowserSettings>,
-        _extra_info: Option<&mut Option<cef::DictionaryValue>>,
+        extra_info: Option<&mut Option<cef::DictionaryValue>>,
         _no_javascript_access: Option<&mut ::std::os::raw::c_int>,
     ) -> ::std::os::raw::c_int {
-        return true as _;
+        let Some(browser) = browser else {
+            return true as _;
+        };
+        let opener: BrowserId = browser.identifier().into();
+        let request = PopupRequest {
+            opener,
+            target_url: target_url.map(|url| url.to_string()).unwrap_or_default(),
+            target_frame_name: target_frame_name
+                .map(|name| name.to_string())
+                .unwrap_or_default(),
+            disposition: target_disposition.into(),
+            user_gesture: user_gesture == 1,
+            features: popup_features.map(Into::into).unwrap_or_default(),
+        };
+        let action = self
+            .handler
+            .options
+            .popup_policy
+            .as_ref()
+            .map(|policy| policy(&request))
+            .unwrap_or_default();
+        tracing::info!(?request, ?action, "popup requested");
+
+        match action {
+            PopupAction::Deny => {
+                self.handler.send(LifeSpanEvent::PopupRequested { request });
+                true as _
+            }
+            PopupAction::NavigateCurrent => {
+                if let Some(frame) = browser.main_frame() {
+                    frame.load_url(Some(&request.target_url.as_str().into()));
+                }
+                self.handler.send(LifeSpanEvent::PopupRequested { request });
+                true as _
+            }
+            PopupAction::Open => {
+                let (Some(window_info), Some(client)) = (window_info, client) else {
+                    return true as _;
+                };
+                let Some((device_scale_factor, mut view_rect)) =
+                    WEBVIEW_STATES.with_borrow(|states| {
+                        states.get(&opener).map(|state| {
+                            (state.render.device_scale_factor(), state.render.view_rect())
+                        })
+                    })
+                else {
+                    return true as _;
+                };
+                if let Some(width) = request.features.width {
+                    view_rect.width = width;
+                }
+                if let Some(height) = request.features.height {
+                    view_rect.height = height;
+                }
+
+                window_info.windowless_rendering_enabled = true as _;
+                window_info.shared_texture_enabled = true as _;
+                window_info.external_begin_frame_enabled = true as _;
+                window_info.runtime_style =
+                    cef::sys::cef_runtime_style_t::CEF_RUNTIME_STYLE_ALLOY.into();
+                if let Some(extra_info) = extra_info {
+                    *extra_info = self.handler.options.bindings.extra_info();
+                }
+
+                let launch_id = LaunchId::unique();
+                let (popup, mut handlers) = IcyClient::new(
+                    launch_id,
+                    device_scale_factor,
+                    view_rect,
+                    self.handler.options.clone(),
+                );
+                let IcyClient { state, subscribers } = popup;
+                handlers.opened_by(&self.handler);
+                *client = Some(ClientBuilder::build(handlers));
+                new_browser(launch_id, state);
+                self.handler
+                    .pending_popups
+                    .borrow_mut()
+                    .insert((opener, popup_id), launch_id);
+
+                self.handler.send(LifeSpanEvent::PopupOpened {
+                    request,
+                    popup: PopupWebview {
+                        opener,
+                        subscribers: Arc::new(AtomicTake::new(subscribers)),
+                    },
+                });
+                false as _
+            }
+        }
+    }
+
+    fn on_before_popup_aborted(
+        &self,
+        browser: Option<&mut cef::Browser>,
+        popup_id: ::std::os::raw::c_int,
+    ) {
+        let Some(browser) = browser else {
+            return;
+        };
+        let opener: BrowserId = browser.identifier().into();
+        if let Some(launch_id) = self
+            .handler
+            .pending_popups
+            .borrow_mut()
+            .remove(&(opener, popup_id))
+        {
+            abort_browser(launch_id);
+        }
     }
 }
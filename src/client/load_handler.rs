@@ -7,34 +7,49 @@ use cef::{
 use std::ptr::null_mut;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
-#[derive(Debug)]
-#[allow(unused)]
+use crate::BrowserId;
+
+#[derive(Clone, Debug)]
 pub enum LoadEvent {
     Changed {
-        browser_id: Option<i32>,
+        browser_id: BrowserId,
         is_loading: bool,
         can_go_forward: bool,
         can_go_back: bool,
     },
     Start {
-        browser_id: Option<i32>,
+        browser_id: BrowserId,
         frame_id: Option<String>,
+        is_main_frame: bool,
         transition_type: u32,
     },
     End {
-        browser_id: Option<i32>,
+        browser_id: BrowserId,
         frame_id: Option<String>,
+        is_main_frame: bool,
         http_status_code: u16,
     },
     Error {
-        browser_id: Option<i32>,
+        browser_id: BrowserId,
         frame_id: Option<String>,
+        is_main_frame: bool,
         error_code: i32,
         error_text: Option<String>,
         failed_url: Option<String>,
     },
 }
 
+impl LoadEvent {
+    pub fn browser_id(&self) -> BrowserId {
+        match self {
+            Self::Changed { browser_id, .. }
+            | Self::Start { browser_id, .. }
+            | Self::End { browser_id, .. }
+            | Self::Error { browser_id, .. } => *browser_id,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct IcyLoadHandler {
     tx: UnboundedSender<LoadEvent>,
@@ -103,8 +118,11 @@ impl ImplLoadHandler for LoadHandlerBuilder {
         can_go_back: ::std::os::raw::c_int,
         can_go_forward: ::std::os::raw::c_int,
     ) {
+        let Some(browser) = browser else {
+            return;
+        };
         let event = LoadEvent::Changed {
-            browser_id: browser.map(|b| b.identifier()),
+            browser_id: browser.identifier().into(),
             is_loading: is_loading == 1,
             can_go_forward: can_go_forward == 1,
             can_go_back: can_go_back == 1,
@@ -121,13 +139,18 @@ impl ImplLoadHandler for LoadHandlerBuilder {
         frame: Option<&mut Frame>,
         transition_type: cef::TransitionType,
     ) {
+        let Some(browser) = browser else {
+            return;
+        };
+        let Some(frame) = frame else {
+            return;
+        };
         let event = LoadEvent::Start {
-            browser_id: browser.map(|b| b.identifier()),
-
-            frame_id: frame.and_then(|f| {
-                (CefStringUtf8::from(&CefStringUtf16::from(&f.identifier())).as_str())
-                    .map(str::to_string)
-            }),
+            browser_id: browser.identifier().into(),
+            is_main_frame: frame.is_main() == 1,
+            frame_id: CefStringUtf8::from(&CefStringUtf16::from(&frame.identifier()))
+                .as_str()
+                .map(str::to_string),
             transition_type: (*transition_type.as_ref()) as _,
         };
         tracing::info!(?event);
@@ -142,12 +165,18 @@ impl ImplLoadHandler for LoadHandlerBuilder {
         frame: Option<&mut Frame>,
         http_status_code: ::std::os::raw::c_int,
     ) {
+        let Some(browser) = browser else {
+            return;
+        };
+        let Some(frame) = frame else {
+            return;
+        };
         let event = LoadEvent::End {
-            browser_id: browser.map(|b| b.identifier()),
-            frame_id: frame.and_then(|f| {
-                (CefStringUtf8::from(&CefStringUtf16::from(&f.identifier())).as_str())
-                    .map(str::to_string)
-            }),
+            browser_id: browser.identifier().into(),
+            is_main_frame: frame.is_main() == 1,
+            frame_id: CefStringUtf8::from(&CefStringUtf16::from(&frame.identifier()))
+                .as_str()
+                .map(str::to_string),
             http_status_code: http_status_code as _,
         };
         tracing::info!(?event);
@@ -164,13 +193,18 @@ impl ImplLoadHandler for LoadHandlerBuilder {
         error_text: Option<&cef::CefString>,
         failed_url: Option<&cef::CefString>,
     ) {
+        let Some(browser) = browser else {
+            return;
+        };
+        let Some(frame) = frame else {
+            return;
+        };
         let event = LoadEvent::Error {
-            browser_id: browser.map(|b| b.identifier()),
-            frame_id: frame.and_then(|f| {
-                CefStringUtf8::from(&CefStringUtf16::from(&f.identifier()))
-                    .as_str()
-                    .map(|s| s.to_string())
-            }),
+            browser_id: browser.identifier().into(),
+            is_main_frame: frame.is_main() == 1,
+            frame_id: CefStringUtf8::from(&CefStringUtf16::from(&frame.identifier()))
+                .as_str()
+                .map(str::to_string),
             error_code: (*error_code.as_ref()) as _,
             error_text: error_text
                 .map(CefStringUtf8::from)
@@ -181,7 +215,7 @@ impl ImplLoadHandler for LoadHandlerBuilder {
         };
         tracing::info!(?event);
         if let Err(err) = self.load_handler.tx.send(event) {
-            tracing::error!(?err, "cannot send load start event");
+            tracing::error!(?err, "cannot send load error event");
         }
     }
 }
@@ -1,5 +1,4 @@
 use crate::BrowserId;
-use crate::instance::resize;
 use cef;
 use cef::{rc::*, sys, *};
 use iced_wgpu::window::compositor::hack_wgpu::{get_wgpu_device, get_wgpu_queue};
@@ -7,6 +6,7 @@ use std::{
     cell::{Ref, RefCell},
     fmt::Debug,
     ptr::null_mut,
+    sync::Mutex,
 };
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
 
@@ -14,6 +14,8 @@ use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
 pub struct CefFrame {
     browser_id: BrowserId,
     frame: wgpu::BindGroup,
+    /// open popup widget and where it is drawn in the view
+    popup: Option<(wgpu::BindGroup, iced::Rectangle)>,
 }
 
 impl CefFrame {
@@ -21,6 +23,10 @@ impl CefFrame {
         self.frame.clone()
     }
 
+    pub fn popup(&self) -> Option<(wgpu::BindGroup, iced::Rectangle)> {
+        self.popup.clone()
+    }
+
     pub fn browser_id(&self) -> BrowserId {
         self.browser_id
     }
@@ -30,6 +36,12 @@ impl CefFrame {
 pub struct IcyRenderHandler {
     state: IcyRenderState,
     tx: UnboundedSender<CefFrame>,
+    paint_target: std::rc::Rc<RefCell<Option<PaintTarget>>>,
+    popup_target: std::rc::Rc<RefCell<Option<PaintTarget>>>,
+    view_frame: std::rc::Rc<RefCell<Option<wgpu::BindGroup>>>,
+    popup_frame: std::rc::Rc<RefCell<Option<wgpu::BindGroup>>>,
+    /// copy of the latest popup frame of the accelerated path, see [`IcyRenderState::texture`]
+    popup_texture: std::rc::Rc<RefCell<Option<wgpu::Texture>>>,
 }
 
 impl IcyRenderHandler {
@@ -46,16 +58,60 @@ impl IcyRenderHandler {
             device_scale_factor,
             view_rect,
             size,
+            texture: std::rc::Rc::new(RefCell::new(None)),
+            popup: std::rc::Rc::new(RefCell::new(None)),
         };
         (
             Self {
                 state: state.clone(),
                 tx,
+                paint_target: std::rc::Rc::new(RefCell::new(None)),
+                popup_target: std::rc::Rc::new(RefCell::new(None)),
+                view_frame: std::rc::Rc::new(RefCell::new(None)),
+                popup_frame: std::rc::Rc::new(RefCell::new(None)),
+                popup_texture: std::rc::Rc::new(RefCell::new(None)),
             },
             state,
             rx,
         )
     }
+
+    /// Send the latest view with the popup on top, if one is open
+    fn send_frame(&self, browser_id: BrowserId) {
+        let Some(frame) = self.view_frame.borrow().clone() else {
+            return;
+        };
+        let popup = self
+            .state
+            .popup()
+            .zip(self.popup_frame.borrow().clone())
+            .map(|(popup, bind_group)| {
+                let rect = popup.shown;
+                (
+                    bind_group,
+                    iced::Rectangle {
+                        x: rect.x as _,
+                        y: rect.y as _,
+                        width: rect.width as _,
+                        height: rect.height as _,
+                    },
+                )
+            });
+        _ = self.tx.send(CefFrame {
+            browser_id,
+            frame,
+            popup,
+        });
+    }
+}
+
+/// Placement of a popup widget like a `<select>` dropdown in view coordinates
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PopupPlacement {
+    /// where cef wants the popup
+    original: cef::Rect,
+    /// moved to fit into the view
+    shown: cef::Rect,
 }
 
 pub struct RenderHandlerBuilder {
@@ -69,6 +125,10 @@ pub struct IcyRenderState {
     pub(crate) device_scale_factor: std::rc::Rc<RefCell<f32>>,
     pub(crate) view_rect: std::rc::Rc<RefCell<cef::Rect>>,
     pub(crate) size: std::rc::Rc<RefCell<(i32, i32)>>,
+    /// copy of the latest frame of the accelerated path, the shared texture is recycled
+    /// by cef once the paint returns
+    pub(crate) texture: std::rc::Rc<RefCell<Option<wgpu::Texture>>>,
+    pub(crate) popup: std::rc::Rc<RefCell<Option<PopupPlacement>>>,
 }
 
 impl Debug for IcyRenderState {
@@ -97,12 +157,95 @@ impl IcyRenderState {
         *self.view_rect.borrow_mut() = view_rect;
     }
 
+    /// Lay the page of `host` out for the widget `bounds`
+    pub(crate) fn resize(&self, host: &BrowserHost, bounds: iced::Rectangle) {
+        if bounds.width <= 0.0 || bounds.height <= 0.0 {
+            return;
+        }
+        self.set_view_rect(cef::Rect {
+            x: bounds.x as i32,
+            y: bounds.y as i32,
+            width: bounds.width as i32,
+            height: bounds.height as i32,
+        });
+        host.was_resized();
+    }
+
+    /// Follow the scale factor of the window the webview is drawn in, e.g. moved to another
+    /// monitor
+    pub(crate) fn rescale(&self, host: &BrowserHost, device_scale_factor: f32) {
+        if device_scale_factor <= 0.0 || self.device_scale_factor() == device_scale_factor {
+            return;
+        }
+        self.set_device_scale_factor(device_scale_factor);
+        host.notify_screen_info_changed();
+        host.was_resized();
+    }
+
     pub fn pixels(&self) -> Ref<'_, Vec<u8>> {
         self.pixels.borrow()
     }
     pub fn size(&self) -> (i32, i32) {
         self.size.borrow().clone()
     }
+
+    pub fn texture(&self) -> Option<wgpu::Texture> {
+        self.texture.borrow().clone()
+    }
+
+    pub(crate) fn popup(&self) -> Option<PopupPlacement> {
+        *self.popup.borrow()
+    }
+
+    /// Map a point over the shown popup to where cef placed it
+    pub(crate) fn popup_point(&self, point: iced::Point) -> iced::Point {
+        let Some(popup) = self.popup() else {
+            return point;
+        };
+        let shown = popup.shown;
+        let inside = point.x >= shown.x as f32
+            && point.y >= shown.y as f32
+            && point.x < (shown.x + shown.width) as f32
+            && point.y < (shown.y + shown.height) as f32;
+        if !inside {
+            return point;
+        }
+        iced::Point::new(
+            point.x + (popup.original.x - shown.x) as f32,
+            point.y + (popup.original.y - shown.y) as f32,
+        )
+    }
+}
+
+/// Copy `frame` into `target`, or a new texture if it does not fit anymore
+fn copy_frame(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    frame: &wgpu::Texture,
+    target: Option<wgpu::Texture>,
+) -> wgpu::Texture {
+    let target = target
+        .filter(|target| target.size() == frame.size() && target.format() == frame.format())
+        .unwrap_or_else(|| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Cef Frame Copy"),
+                size: frame.size(),
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: frame.format(),
+                usage: wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_DST
+                    | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            })
+        });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Cef Frame Copy Command"),
+    });
+    encoder.copy_texture_to_texture(frame.as_image_copy(), target.as_image_copy(), frame.size());
+    queue.submit(Some(encoder.finish()));
+    target
 }
 
 impl RenderHandlerBuilder {
@@ -204,10 +347,11 @@ impl ImplRenderHandler for RenderHandlerBuilder {
             return;
         };
 
+        let is_popup = type_ == cef::sys::cef_paint_element_type_t::PET_POPUP.into();
         let src_texture = {
             use cef::osr_texture_import::shared_texture_handle::SharedTextureHandle;
 
-            if type_ != PaintElementType::default() {
+            if type_ != PaintElementType::default() && !is_popup {
                 return;
             }
 
@@ -224,7 +368,20 @@ impl ImplRenderHandler for RenderHandlerBuilder {
             }
         };
 
-        let dst_texture = src_texture;
+        let Some(queue) = get_wgpu_queue() else {
+            return;
+        };
+        // bind a copy, the shared texture is recycled by cef once the paint returns
+        let target = match is_popup {
+            true => &self.handler.popup_texture,
+            false => &self.handler.state.texture,
+        };
+        let dst_texture = copy_frame(device, queue, &src_texture, target.take());
+        target.replace(Some(dst_texture.clone()));
+        if !is_popup {
+            // screenshots follow whichever path painted the view last
+            self.handler.state.pixels.borrow_mut().clear();
+        }
         /*
         let texture_desc = wgpu::TextureDescriptor {
                   label: Some("Cef Texture"),
@@ -263,69 +420,65 @@ impl ImplRenderHandler for RenderHandlerBuilder {
         queue.submit(Some(copy.finish()));
         device.poll(wgpu::PollType::Wait).unwrap();
         */
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
-            mipmap_filter: wgpu::FilterMode::Linear,
-            ..Default::default()
-        });
-        let texture_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("Cef Texture Bind Group Layout"),
-                entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Texture {
-                            multisampled: false,
-                            view_dimension: wgpu::TextureViewDimension::D2,
-                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        },
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                        count: None,
-                    },
-                ],
-            });
+        let bind_group = TextureBinding::get(device).bind_group(
+            device,
+            &dst_texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("Cef Texture View"),
+                ..Default::default()
+            }),
+        );
 
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Cef Texture Bind Group"),
-            layout: &texture_bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&dst_texture.create_view(
-                        &wgpu::TextureViewDescriptor {
-                            label: Some("Cef Texture View"),
-                            ..Default::default()
-                        },
-                    )),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&sampler),
-                },
-            ],
-        });
+        let frame = match is_popup {
+            true => &self.handler.popup_frame,
+            false => &self.handler.view_frame,
+        };
+        frame.replace(Some(bind_group));
+        self.handler.send_frame(browser_id);
+    }
 
-        _ = self.handler.tx.send(CefFrame {
-            browser_id,
-            frame: bind_group,
-        });
+    fn on_popup_show(&self, browser: Option<&mut Browser>, show: ::std::os::raw::c_int) {
+        let Some(browser) = browser else {
+            return;
+        };
+        if show == 1 {
+            return;
+        }
+        self.handler.state.popup.replace(None);
+        self.handler.popup_frame.replace(None);
+        self.handler.popup_texture.replace(None);
+        self.handler.popup_target.replace(None);
+        self.handler.send_frame(browser.identifier().into());
+    }
+
+    fn on_popup_size(&self, _browser: Option<&mut Browser>, rect: Option<&Rect>) {
+        let Some(rect) = rect else {
+            return;
+        };
+        if rect.width <= 0 || rect.height <= 0 {
+            return;
+        }
+        let view_rect = self.handler.state.view_rect();
+        let mut shown = rect.clone();
+        // keep the popup inside the view, input is shifted back by `popup_point`
+        if shown.x + shown.width > view_rect.width {
+            shown.x = view_rect.width - shown.width;
+        }
+        if shown.y + shown.height > view_rect.height {
+            shown.y = view_rect.height - shown.height;
+        }
+        shown.x = shown.x.max(0);
+        shown.y = shown.y.max(0);
+        self.handler.state.popup.replace(Some(PopupPlacement {
+            original: rect.clone(),
+            shown,
+        }));
     }
 
     fn on_paint(
         &self,
         browser: Option<&mut Browser>,
         type_: PaintElementType,
-        _dirty_rects: Option<&[Rect]>,
+        dirty_rects: Option<&[Rect]>,
         buffer: *const u8,
         width: ::std::os::raw::c_int,
         height: ::std::os::raw::c_int,
@@ -340,10 +493,10 @@ impl ImplRenderHandler for RenderHandlerBuilder {
             return;
         };
         let browser_id: BrowserId = browser.identifier().into();
-        if type_ != cef::sys::cef_paint_element_type_t::PET_VIEW.into() {
+        let is_popup = type_ == cef::sys::cef_paint_element_type_t::PET_POPUP.into();
+        if type_ != cef::sys::cef_paint_element_type_t::PET_VIEW.into() && !is_popup {
             return;
         }
-        use wgpu::{Extent3d, TextureDimension, TextureUsages};
 
         if buffer.is_null() || width <= 0 || height <= 0 {
             return;
@@ -351,41 +504,173 @@ impl ImplRenderHandler for RenderHandlerBuilder {
 
         let buffer_size = (width * height * 4) as usize; // BGRA format
         let buffer_slice = unsafe { std::slice::from_raw_parts(buffer, buffer_size) };
+        let (width, height) = (width as u32, height as u32);
 
-        let texture_desc = wgpu::TextureDescriptor {
+        let mut paint_target = match is_popup {
+            true => self.handler.popup_target.borrow_mut(),
+            false => self.handler.paint_target.borrow_mut(),
+        };
+        let resized = paint_target
+            .as_ref()
+            .is_none_or(|target| target.width != width || target.height != height);
+        if resized {
+            paint_target.replace(PaintTarget::new(device, width, height));
+        }
+        let Some(target) = paint_target.as_ref() else {
+            return;
+        };
+
+        // a new texture starts empty, so it needs the whole frame
+        let full_frame = [Rect {
+            x: 0,
+            y: 0,
+            width: width as _,
+            height: height as _,
+        }];
+        let dirty_rects = match dirty_rects {
+            Some(rects) if !resized && !rects.is_empty() => rects,
+            _ => &full_frame[..],
+        };
+
+        // the copy of the view kept for screenshots follows the texture
+        let mut pixels = self.handler.state.pixels.borrow_mut();
+        let copy_full_frame = !is_popup && (resized || pixels.len() != buffer_size);
+        if copy_full_frame {
+            pixels.clear();
+            pixels.extend_from_slice(buffer_slice);
+            *self.handler.state.size.borrow_mut() = (width as _, height as _);
+        }
+        if !is_popup {
+            self.handler.state.texture.replace(None);
+        }
+        for rect in dirty_rects {
+            let Some(rect) = clamp_rect(rect, width, height) else {
+                continue;
+            };
+            let offset = (rect.y as usize * width as usize + rect.x as usize) * 4;
+            queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &target.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: rect.x as _,
+                        y: rect.y as _,
+                        z: 0,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                buffer_slice,
+                wgpu::TexelCopyBufferLayout {
+                    offset: offset as _,
+                    bytes_per_row: Some(4 * width),
+                    rows_per_image: Some(height),
+                },
+                wgpu::Extent3d {
+                    width: rect.width as _,
+                    height: rect.height as _,
+                    depth_or_array_layers: 1,
+                },
+            );
+            if !is_popup && !copy_full_frame {
+                let stride = width as usize * 4;
+                let row_len = rect.width as usize * 4;
+                for row in rect.y as usize..(rect.y + rect.height) as usize {
+                    let start = row * stride + rect.x as usize * 4;
+                    pixels[start..start + row_len]
+                        .copy_from_slice(&buffer_slice[start..start + row_len]);
+                }
+            }
+        }
+
+        let frame = match is_popup {
+            true => &self.handler.popup_frame,
+            false => &self.handler.view_frame,
+        };
+        frame.replace(Some(target.bind_group.clone()));
+        drop(pixels);
+        drop(paint_target);
+        self.handler.send_frame(browser_id);
+    }
+}
+
+/// `rect` limited to a `width`x`height` frame, `None` if nothing is left
+fn clamp_rect(rect: &Rect, width: u32, height: u32) -> Option<Rect> {
+    let x = rect.x.clamp(0, width as _);
+    let y = rect.y.clamp(0, height as _);
+    let right = (rect.x + rect.width).clamp(x, width as _);
+    let bottom = (rect.y + rect.height).clamp(y, height as _);
+    (right > x && bottom > y).then_some(Rect {
+        x,
+        y,
+        width: right - x,
+        height: bottom - y,
+    })
+}
+
+/// Texture the software path paints into, kept until the view is resized
+struct PaintTarget {
+    texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+    width: u32,
+    height: u32,
+}
+
+impl PaintTarget {
+    fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("CEF Paint Texture"),
-            size: Extent3d {
-                width: width as u32,
-                height: height as u32,
+            size: wgpu::Extent3d {
+                width,
+                height,
                 depth_or_array_layers: 1,
             },
             mip_level_count: 1,
             sample_count: 1,
-            dimension: TextureDimension::D2,
+            dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Bgra8Unorm,
-            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
             view_formats: &[],
-        };
+        });
+        let bind_group = TextureBinding::get(device).bind_group(
+            device,
+            &texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("Cef Texture View"),
+                ..Default::default()
+            }),
+        );
+        Self {
+            texture,
+            bind_group,
+            width,
+            height,
+        }
+    }
+}
 
-        let src_texture = device.create_texture(&texture_desc);
-        let dst_texture = src_texture;
+/// Sampler and bind group layout shared by every frame texture of a device
+#[derive(Clone)]
+struct TextureBinding {
+    sampler: wgpu::Sampler,
+    layout: wgpu::BindGroupLayout,
+}
 
-        queue.write_texture(
-            wgpu::TexelCopyTextureInfo {
-                texture: &dst_texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            buffer_slice,
-            wgpu::TexelCopyBufferLayout {
-                offset: 0,
-                bytes_per_row: Some(4 * width as u32),
-                rows_per_image: Some(height as u32),
-            },
-            texture_desc.size,
-        );
+/// Bindings by device, a layout cannot be used with another device
+static TEXTURE_BINDINGS: Mutex<Vec<(wgpu::Device, TextureBinding)>> = Mutex::new(Vec::new());
 
+impl TextureBinding {
+    fn get(device: &wgpu::Device) -> Self {
+        let mut bindings = TEXTURE_BINDINGS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some((_, binding)) = bindings.iter().find(|(known, _)| known == device) {
+            return binding.clone();
+        }
+        let binding = Self::new(device);
+        bindings.push((device.clone(), binding.clone()));
+        binding
+    }
+
+    fn new(device: &wgpu::Device) -> Self {
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
             address_mode_u: wgpu::AddressMode::ClampToEdge,
             address_mode_v: wgpu::AddressMode::ClampToEdge,
@@ -395,86 +680,58 @@ impl ImplRenderHandler for RenderHandlerBuilder {
             mipmap_filter: wgpu::FilterMode::Linear,
             ..Default::default()
         });
-        let texture_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("Cef Texture Bind Group Layout"),
-                entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Texture {
-                            multisampled: false,
-                            view_dimension: wgpu::TextureViewDimension::D2,
-                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        },
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                        count: None,
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Cef Texture Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
                     },
-                ],
-            });
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        Self { sampler, layout }
+    }
 
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+    fn bind_group(&self, device: &wgpu::Device, view: &wgpu::TextureView) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("Cef Texture Bind Group"),
-            layout: &texture_bind_group_layout,
+            layout: &self.layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&dst_texture.create_view(
-                        &wgpu::TextureViewDescriptor {
-                            label: Some("Cef Texture View"),
-                            ..Default::default()
-                        },
-                    )),
+                    resource: wgpu::BindingResource::TextureView(view),
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&sampler),
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
                 },
             ],
-        });
-
-        _ = self.handler.tx.send(CefFrame {
-            browser_id,
-            frame: bind_group,
-        });
+        })
     }
 }
 
 pub struct CefPipeline {
     pipeline: wgpu::RenderPipeline,
     quad: Geometry,
-    bounds: iced::Rectangle<f32>,
+    scale_factor: f32,
 }
 
 impl CefPipeline {
     pub fn new(device: &wgpu::Device) -> Self {
-        let texture_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("Cef Texture Bind Group Layout"),
-                entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Texture {
-                            multisampled: false,
-                            view_dimension: wgpu::TextureViewDimension::D2,
-                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        },
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                        count: None,
-                    },
-                ],
-            });
+        let texture_binding = TextureBinding::get(device);
+        let texture_bind_group_layout = &texture_binding.layout;
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Cef Shader"),
             source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
@@ -483,7 +740,7 @@ impl CefPipeline {
         let render_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("Cef Pipeline Layout"),
-                bind_group_layouts: &[&texture_bind_group_layout],
+                bind_group_layouts: &[texture_bind_group_layout],
                 push_constant_ranges: &[],
             });
         let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
@@ -529,7 +786,7 @@ impl CefPipeline {
         Self {
             pipeline,
             quad: Geometry::new(device),
-            bounds: iced::Rectangle::INFINITE,
+            scale_factor: 1.0,
         }
     }
 
@@ -544,20 +801,48 @@ impl CefPipeline {
         render_pass.set_vertex_buffer(0, self.quad.vertex_buffer.slice(..));
         render_pass.draw(0..self.quad.vertex_count, 0..1);
     }
+
+    /// Draw the popup at `rect` of the view placed at `bounds`, both in logical pixels
+    fn render_popup(
+        &self,
+        bind_group: &wgpu::BindGroup,
+        render_pass: &mut wgpu::RenderPass,
+        bounds: iced::Rectangle<f32>,
+        rect: iced::Rectangle<f32>,
+    ) {
+        let scale = self.scale_factor;
+        render_pass.set_viewport(
+            (bounds.x + rect.x) * scale,
+            (bounds.y + rect.y) * scale,
+            rect.width * scale,
+            rect.height * scale,
+            0.0,
+            1.0,
+        );
+        self.render(bind_group, render_pass, rect);
+        render_pass.set_viewport(
+            bounds.x * scale,
+            bounds.y * scale,
+            bounds.width * scale,
+            bounds.height * scale,
+            0.0,
+            1.0,
+        );
+    }
 }
 
 #[derive(Debug)]
 pub struct Primitive {
     bind_group: wgpu::BindGroup,
-    browser_id: BrowserId,
+    popup: Option<(wgpu::BindGroup, iced::Rectangle)>,
     bounds: iced::Rectangle,
 }
 
 impl Primitive {
-    fn new(bind_group: wgpu::BindGroup, browser_id: BrowserId, bounds: iced::Rectangle) -> Self {
+    fn new(frame: &CefFrame, bounds: iced::Rectangle) -> Self {
         Self {
-            bind_group,
-            browser_id,
+            bind_group: frame.frame.clone(),
+            popup: frame.popup.clone(),
             bounds,
         }
     }
@@ -573,7 +858,7 @@ impl<Message> iced::widget::shader::Program<Message> for CefFrame {
         _cursor: iced::mouse::Cursor,
         bounds: iced::Rectangle,
     ) -> Self::Primitive {
-        Primitive::new(self.frame.clone(), self.browser_id, bounds)
+        Primitive::new(self, bounds)
     }
 
     fn update(
@@ -606,17 +891,18 @@ impl iced::widget::shader::Primitive for Primitive {
         pipeline: &mut Self::Renderer,
         _device: &wgpu::Device,
         _queue: &wgpu::Queue,
-        bounds: &iced::Rectangle,
-        _viewport: &iced::widget::shader::Viewport,
+        _bounds: &iced::Rectangle,
+        viewport: &iced::widget::shader::Viewport,
     ) {
-        if pipeline.bounds != *bounds {
-            pipeline.bounds = *bounds;
-            resize(self.browser_id, *bounds);
-        }
+        // the webview widget resizes the browser, this only places the quad
+        pipeline.scale_factor = viewport.scale_factor() as _;
     }
 
     fn draw(&self, renderer: &Self::Renderer, render_pass: &mut wgpu::RenderPass<'_>) -> bool {
         renderer.render(&self.bind_group, render_pass, self.bounds);
+        if let Some((popup, rect)) = &self.popup {
+            renderer.render_popup(popup, render_pass, self.bounds, *rect);
+        }
         true
     }
 
@@ -0,0 +1,299 @@
+use cef;
+use cef::{
+    ImplRequestHandler, ImplResourceRequestHandler, RequestHandler, ResourceRequestHandler,
+    WrapRequestHandler, WrapResourceRequestHandler,
+    rc::{Rc, RcImpl},
+    sys, *,
+};
+use std::{ptr::null_mut, sync::Arc};
+
+use crate::{
+    BrowserId,
+    request::{request_headers, response_headers, set_request_headers},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResourceType {
+    MainFrame,
+    SubFrame,
+    Stylesheet,
+    Script,
+    Image,
+    Font,
+    SubResource,
+    Media,
+    Worker,
+    Favicon,
+    Xhr,
+    Ping,
+    ServiceWorker,
+    Other,
+}
+
+impl From<cef::ResourceType> for ResourceType {
+    fn from(type_: cef::ResourceType) -> Self {
+        use cef::sys::cef_resource_type_t;
+        match type_.as_ref() {
+            cef_resource_type_t::RT_MAIN_FRAME => Self::MainFrame,
+            cef_resource_type_t::RT_SUB_FRAME => Self::SubFrame,
+            cef_resource_type_t::RT_STYLESHEET => Self::Stylesheet,
+            cef_resource_type_t::RT_SCRIPT => Self::Script,
+            cef_resource_type_t::RT_IMAGE => Self::Image,
+            cef_resource_type_t::RT_FONT_RESOURCE => Self::Font,
+            cef_resource_type_t::RT_SUB_RESOURCE => Self::SubResource,
+            cef_resource_type_t::RT_MEDIA => Self::Media,
+            cef_resource_type_t::RT_WORKER | cef_resource_type_t::RT_SHARED_WORKER => Self::Worker,
+            cef_resource_type_t::RT_FAVICON => Self::Favicon,
+            cef_resource_type_t::RT_XHR => Self::Xhr,
+            cef_resource_type_t::RT_PING => Self::Ping,
+            cef_resource_type_t::RT_SERVICE_WORKER => Self::ServiceWorker,
+            _ => Self::Other,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct InterceptedRequest {
+    pub browser_id: Option<BrowserId>,
+    pub frame_id: Option<String>,
+    pub is_main_frame: bool,
+    pub is_navigation: bool,
+    pub url: String,
+    pub method: String,
+    pub headers: Vec<(String, String)>,
+    pub resource_type: ResourceType,
+}
+
+#[derive(Clone, Debug)]
+pub struct InterceptedResponse {
+    pub status: u16,
+    pub status_text: String,
+    pub mime_type: String,
+    pub headers: Vec<(String, String)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RequestAction {
+    Continue,
+    Cancel,
+    Redirect(String),
+}
+
+/// Hook into every resource request of a webview, called on the cef IO thread.
+///
+/// The url, method and headers modified in [`RequestInterceptor::on_request`] are sent
+/// with the request, the other fields are informational.
+pub trait RequestInterceptor: Send + Sync + 'static {
+    fn on_request(&self, request: &mut InterceptedRequest) -> RequestAction;
+
+    fn on_response(&self, _request: &InterceptedRequest, _response: &InterceptedResponse) {}
+}
+
+fn intercepted_request(
+    browser: Option<&mut Browser>,
+    frame: Option<&mut Frame>,
+    request: &Request,
+    is_navigation: bool,
+) -> InterceptedRequest {
+    InterceptedRequest {
+        browser_id: browser.map(|browser| browser.identifier().into()),
+        frame_id: frame.as_ref().and_then(|frame| {
+            CefStringUtf8::from(&CefStringUtf16::from(&frame.identifier()))
+                .as_str()
+                .map(str::to_string)
+        }),
+        is_main_frame: frame.is_some_and(|frame| frame.is_main() == 1),
+        is_navigation,
+        url: CefString::from(&request.url()).to_string(),
+        method: CefString::from(&request.method()).to_string(),
+        headers: request_headers(request),
+        resource_type: request.resource_type().into(),
+    }
+}
+
+#[derive(Clone)]
+pub struct IcyRequestHandler {
+    interceptor: Arc<dyn RequestInterceptor>,
+}
+
+impl IcyRequestHandler {
+    pub fn new(interceptor: Arc<dyn RequestInterceptor>) -> Self {
+        Self { interceptor }
+    }
+}
+
+pub(crate) struct RequestHandlerBuilder {
+    object: *mut RcImpl<sys::_cef_request_handler_t, Self>,
+    handler: IcyRequestHandler,
+}
+
+impl RequestHandlerBuilder {
+    pub(crate) fn build(handler: IcyRequestHandler) -> RequestHandler {
+        RequestHandler::new(Self {
+            object: null_mut(),
+            handler,
+        })
+    }
+}
+
+impl WrapRequestHandler for RequestHandlerBuilder {
+    fn wrap_rc(&mut self, object: *mut RcImpl<sys::_cef_request_handler_t, Self>) {
+        self.object = object;
+    }
+}
+
+impl Rc for RequestHandlerBuilder {
+    fn as_base(&self) -> &sys::cef_base_ref_counted_t {
+        unsafe {
+            let base = &*self.object;
+            std::mem::transmute(&base.cef_object)
+        }
+    }
+}
+
+impl Clone for RequestHandlerBuilder {
+    fn clone(&self) -> Self {
+        let object = unsafe {
+            let rc_impl = &mut *self.object;
+            rc_impl.interface.add_ref();
+            rc_impl
+        };
+
+        Self {
+            object,
+            handler: self.handler.clone(),
+        }
+    }
+}
+
+impl ImplRequestHandler for RequestHandlerBuilder {
+    fn get_raw(&self) -> *mut sys::_cef_request_handler_t {
+        self.object.cast()
+    }
+
+    fn resource_request_handler(
+        &self,
+        _browser: Option<&mut Browser>,
+        _frame: Option<&mut Frame>,
+        _request: Option<&mut Request>,
+        is_navigation: ::std::os::raw::c_int,
+        _is_download: ::std::os::raw::c_int,
+        _request_initiator: Option<&CefString>,
+        _disable_default_handling: Option<&mut ::std::os::raw::c_int>,
+    ) -> Option<ResourceRequestHandler> {
+        Some(ResourceRequestHandlerBuilder::build(
+            self.handler.interceptor.clone(),
+            is_navigation == 1,
+        ))
+    }
+}
+
+pub(crate) struct ResourceRequestHandlerBuilder {
+    object: *mut RcImpl<sys::_cef_resource_request_handler_t, Self>,
+    interceptor: Arc<dyn RequestInterceptor>,
+    is_navigation: bool,
+}
+
+impl ResourceRequestHandlerBuilder {
+    pub(crate) fn build(
+        interceptor: Arc<dyn RequestInterceptor>,
+        is_navigation: bool,
+    ) -> ResourceRequestHandler {
+        ResourceRequestHandler::new(Self {
+            object: null_mut(),
+            interceptor,
+            is_navigation,
+        })
+    }
+}
+
+impl WrapResourceRequestHandler for ResourceRequestHandlerBuilder {
+    fn wrap_rc(&mut self, object: *mut RcImpl<sys::_cef_resource_request_handler_t, Self>) {
+        self.object = object;
+    }
+}
+
+impl Rc for ResourceRequestHandlerBuilder {
+    fn as_base(&self) -> &sys::cef_base_ref_counted_t {
+        unsafe {
+            let base = &*self.object;
+            std::mem::transmute(&base.cef_object)
+        }
+    }
+}
+
+impl Clone for ResourceRequestHandlerBuilder {
+    fn clone(&self) -> Self {
+        let object = unsafe {
+            let rc_impl = &mut *self.object;
+            rc_impl.interface.add_ref();
+            rc_impl
+        };
+
+        Self {
+            object,
+            interceptor: self.interceptor.clone(),
+            is_navigation: self.is_navigation,
+        }
+    }
+}
+
+impl ImplResourceRequestHandler for ResourceRequestHandlerBuilder {
+    fn get_raw(&self) -> *mut sys::_cef_resource_request_handler_t {
+        self.object.cast()
+    }
+
+    fn on_before_resource_load(
+        &self,
+        browser: Option<&mut Browser>,
+        frame: Option<&mut Frame>,
+        request: Option<&mut Request>,
+        _callback: Option<&mut Callback>,
+    ) -> ReturnValue {
+        let Some(request) = request else {
+            return sys::cef_return_value_t::RV_CONTINUE.into();
+        };
+        let mut intercepted = intercepted_request(browser, frame, request, self.is_navigation);
+        let original = intercepted.clone();
+        let action = self.interceptor.on_request(&mut intercepted);
+        tracing::debug!(url = intercepted.url, ?action, "intercept request");
+        if intercepted.url != original.url {
+            request.set_url(Some(&intercepted.url.as_str().into()));
+        }
+        if intercepted.method != original.method {
+            request.set_method(Some(&intercepted.method.as_str().into()));
+        }
+        if intercepted.headers != original.headers {
+            set_request_headers(request, &intercepted.headers);
+        }
+        match action {
+            RequestAction::Continue => sys::cef_return_value_t::RV_CONTINUE.into(),
+            RequestAction::Cancel => sys::cef_return_value_t::RV_CANCEL.into(),
+            RequestAction::Redirect(url) => {
+                request.set_url(Some(&url.as_str().into()));
+                sys::cef_return_value_t::RV_CONTINUE.into()
+            }
+        }
+    }
+
+    fn on_resource_response(
+        &self,
+        browser: Option<&mut Browser>,
+        frame: Option<&mut Frame>,
+        request: Option<&mut Request>,
+        response: Option<&mut Response>,
+    ) -> ::std::os::raw::c_int {
+        let (Some(request), Some(response)) = (request, response) else {
+            return false as _;
+        };
+        let request = intercepted_request(browser, frame, request, self.is_navigation);
+        let response = InterceptedResponse {
+            status: response.status() as _,
+            status_text: CefString::from(&response.status_text()).to_string(),
+            mime_type: CefString::from(&response.mime_type()).to_string(),
+            headers: response_headers(response),
+        };
+        self.interceptor.on_response(&request, &response);
+        false as _
+    }
+}
@@ -0,0 +1,480 @@
+//! Cookies of a webview's request context
+
+use cef::{
+    self, CompletionCallback, CookieVisitor, DeleteCookiesCallback, ImplCompletionCallback,
+    ImplCookieManager, ImplCookieVisitor, ImplDeleteCookiesCallback, ImplRequestContext,
+    ImplSetCookieCallback, SetCookieCallback, WrapCompletionCallback, WrapCookieVisitor,
+    WrapDeleteCookiesCallback, WrapSetCookieCallback,
+    rc::{Rc, RcImpl},
+    sys,
+};
+use std::{
+    ptr::null_mut,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+use tokio::sync::oneshot;
+
+use crate::error::CookieError;
+
+/// Microseconds between 1601-01-01(cef base time) and 1970-01-01
+const UNIX_EPOCH_OFFSET_MICROS: i64 = 11_644_473_600_000_000;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SameSite {
+    #[default]
+    Unspecified,
+    NoRestriction,
+    Lax,
+    Strict,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub path: String,
+    pub secure: bool,
+    pub http_only: bool,
+    pub expires: Option<SystemTime>,
+    pub same_site: SameSite,
+}
+
+impl Cookie {
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+            path: "/".into(),
+            ..Default::default()
+        }
+    }
+
+    fn from_cef(cookie: &cef::Cookie) -> Self {
+        let expires = (cookie.has_expires == 1)
+            .then(|| cookie.expires.val - UNIX_EPOCH_OFFSET_MICROS)
+            .filter(|micros| *micros >= 0)
+            .map(|micros| SystemTime::UNIX_EPOCH + Duration::from_micros(micros as _));
+        Self {
+            name: cookie.name.to_string(),
+            value: cookie.value.to_string(),
+            domain: cookie.domain.to_string(),
+            path: cookie.path.to_string(),
+            secure: cookie.secure == 1,
+            http_only: cookie.httponly == 1,
+            expires,
+            same_site: match cookie.same_site.as_ref() {
+                sys::cef_cookie_same_site_t::CEF_COOKIE_SAME_SITE_NO_RESTRICTION => {
+                    SameSite::NoRestriction
+                }
+                sys::cef_cookie_same_site_t::CEF_COOKIE_SAME_SITE_LAX_MODE => SameSite::Lax,
+                sys::cef_cookie_same_site_t::CEF_COOKIE_SAME_SITE_STRICT_MODE => SameSite::Strict,
+                _ => SameSite::Unspecified,
+            },
+        }
+    }
+
+    fn to_cef(&self) -> cef::Cookie {
+        let expires = self.expires.and_then(|expires| {
+            expires
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .ok()
+                .map(|since| since.as_micros() as i64 + UNIX_EPOCH_OFFSET_MICROS)
+        });
+        cef::Cookie {
+            name: self.name.as_str().into(),
+            value: self.value.as_str().into(),
+            domain: self.domain.as_str().into(),
+            path: self.path.as_str().into(),
+            secure: self.secure as _,
+            httponly: self.http_only as _,
+            has_expires: expires.is_some() as _,
+            expires: cef::Basetime {
+                val: expires.unwrap_or_default(),
+            },
+            same_site: match self.same_site {
+                SameSite::Unspecified => {
+                    sys::cef_cookie_same_site_t::CEF_COOKIE_SAME_SITE_UNSPECIFIED
+                }
+                SameSite::NoRestriction => {
+                    sys::cef_cookie_same_site_t::CEF_COOKIE_SAME_SITE_NO_RESTRICTION
+                }
+                SameSite::Lax => sys::cef_cookie_same_site_t::CEF_COOKIE_SAME_SITE_LAX_MODE,
+                SameSite::Strict => sys::cef_cookie_same_site_t::CEF_COOKIE_SAME_SITE_STRICT_MODE,
+            }
+            .into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Cookie store of a webview's request context
+#[derive(Clone)]
+pub struct CookieManager {
+    manager: cef::CookieManager,
+}
+
+impl std::fmt::Debug for CookieManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CookieManager").finish()
+    }
+}
+
+impl CookieManager {
+    pub(crate) fn new(request_context: &cef::RequestContext) -> Option<Self> {
+        request_context
+            .cookie_manager(None::<&mut CompletionCallback>)
+            .map(|manager| Self { manager })
+    }
+
+    /// Cookies sent with requests to `url`
+    pub fn cookies(
+        &self,
+        url: &str,
+        include_http_only: bool,
+    ) -> impl Future<Output = Result<Vec<Cookie>, CookieError>> + Send + 'static {
+        let (mut visitor, rx) = CookieVisitorBuilder::build();
+        let ret = self.manager.visit_url_cookies(
+            Some(&url.into()),
+            include_http_only as _,
+            Some(&mut visitor),
+        );
+        async move {
+            if ret != 1 {
+                return Err(CookieError::Failed);
+            }
+            rx.await.map_err(|_| CookieError::Cancelled)
+        }
+    }
+
+    pub fn all_cookies(
+        &self,
+    ) -> impl Future<Output = Result<Vec<Cookie>, CookieError>> + Send + 'static {
+        let (mut visitor, rx) = CookieVisitorBuilder::build();
+        let ret = self.manager.visit_all_cookies(Some(&mut visitor));
+        async move {
+            if ret != 1 {
+                return Err(CookieError::Failed);
+            }
+            rx.await.map_err(|_| CookieError::Cancelled)
+        }
+    }
+
+    pub fn set_cookie(
+        &self,
+        url: &str,
+        cookie: &Cookie,
+    ) -> impl Future<Output = Result<(), CookieError>> + Send + 'static {
+        let (tx, rx) = oneshot::channel();
+        let mut callback = SetCookieCallbackBuilder::build(tx);
+        let ret = self.manager.set_cookie(
+            Some(&url.into()),
+            Some(&cookie.to_cef()),
+            Some(&mut callback),
+        );
+        async move {
+            if ret != 1 {
+                return Err(CookieError::Failed);
+            }
+            match rx.await {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(CookieError::Failed),
+                Err(_) => Err(CookieError::Cancelled),
+            }
+        }
+    }
+
+    /// Delete cookies matching `url` and `name`, all cookies if both are `None`.
+    /// Resolves with the number of deleted cookies.
+    pub fn delete_cookies(
+        &self,
+        url: Option<&str>,
+        name: Option<&str>,
+    ) -> impl Future<Output = Result<usize, CookieError>> + Send + 'static {
+        let (tx, rx) = oneshot::channel();
+        let mut callback = DeleteCookiesCallbackBuilder::build(tx);
+        let ret = self.manager.delete_cookies(
+            url.map(cef::CefString::from).as_ref(),
+            name.map(cef::CefString::from).as_ref(),
+            Some(&mut callback),
+        );
+        async move {
+            if ret != 1 {
+                return Err(CookieError::Failed);
+            }
+            rx.await.map_err(|_| CookieError::Cancelled)
+        }
+    }
+
+    /// Flush the backing store to disk
+    pub fn flush(&self) -> impl Future<Output = Result<(), CookieError>> + Send + 'static {
+        let (tx, rx) = oneshot::channel();
+        let mut callback = CompletionCallbackBuilder::build(tx);
+        let ret = self.manager.flush_store(Some(&mut callback));
+        async move {
+            if ret != 1 {
+                return Err(CookieError::Failed);
+            }
+            rx.await.map_err(|_| CookieError::Cancelled)
+        }
+    }
+}
+
+/// Collected cookies are sent once the visitor is released by cef,
+/// which also covers the case that no cookie is visited at all.
+struct CookieCollector {
+    cookies: Mutex<Vec<Cookie>>,
+    tx: Mutex<Option<oneshot::Sender<Vec<Cookie>>>>,
+}
+
+impl Drop for CookieCollector {
+    fn drop(&mut self) {
+        let cookies = self
+            .cookies
+            .get_mut()
+            .map(std::mem::take)
+            .unwrap_or_default();
+        if let Some(tx) = self.tx.get_mut().ok().and_then(Option::take) {
+            _ = tx.send(cookies);
+        }
+    }
+}
+
+struct CookieVisitorBuilder {
+    object: *mut RcImpl<sys::_cef_cookie_visitor_t, Self>,
+    collector: Arc<CookieCollector>,
+}
+
+impl CookieVisitorBuilder {
+    fn build() -> (CookieVisitor, oneshot::Receiver<Vec<Cookie>>) {
+        let (tx, rx) = oneshot::channel();
+        let visitor = CookieVisitor::new(Self {
+            object: null_mut(),
+            collector: Arc::new(CookieCollector {
+                cookies: Mutex::new(Vec::new()),
+                tx: Mutex::new(Some(tx)),
+            }),
+        });
+        (visitor, rx)
+    }
+}
+
+impl WrapCookieVisitor for CookieVisitorBuilder {
+    fn wrap_rc(&mut self, object: *mut RcImpl<sys::_cef_cookie_visitor_t, Self>) {
+        self.object = object;
+    }
+}
+
+impl Rc for CookieVisitorBuilder {
+    fn as_base(&self) -> &sys::cef_base_ref_counted_t {
+        unsafe {
+            let base = &*self.object;
+            std::mem::transmute(&base.cef_object)
+        }
+    }
+}
+
+impl Clone for CookieVisitorBuilder {
+    fn clone(&self) -> Self {
+        let object = unsafe {
+            let rc_impl = &mut *self.object;
+            rc_impl.interface.add_ref();
+            rc_impl
+        };
+
+        Self {
+            object,
+            collector: self.collector.clone(),
+        }
+    }
+}
+
+impl ImplCookieVisitor for CookieVisitorBuilder {
+    fn get_raw(&self) -> *mut sys::_cef_cookie_visitor_t {
+        self.object.cast()
+    }
+
+    fn visit(
+        &self,
+        cookie: Option<&cef::Cookie>,
+        _count: ::std::os::raw::c_int,
+        _total: ::std::os::raw::c_int,
+        _delete_cookie: Option<&mut ::std::os::raw::c_int>,
+    ) -> ::std::os::raw::c_int {
+        if let Some(cookie) = cookie
+            && let Ok(mut cookies) = self.collector.cookies.lock()
+        {
+            cookies.push(Cookie::from_cef(cookie));
+        }
+        true as _
+    }
+}
+
+struct SetCookieCallbackBuilder {
+    object: *mut RcImpl<sys::_cef_set_cookie_callback_t, Self>,
+    tx: Arc<Mutex<Option<oneshot::Sender<bool>>>>,
+}
+
+impl SetCookieCallbackBuilder {
+    fn build(tx: oneshot::Sender<bool>) -> SetCookieCallback {
+        SetCookieCallback::new(Self {
+            object: null_mut(),
+            tx: Arc::new(Mutex::new(Some(tx))),
+        })
+    }
+}
+
+impl WrapSetCookieCallback for SetCookieCallbackBuilder {
+    fn wrap_rc(&mut self, object: *mut RcImpl<sys::_cef_set_cookie_callback_t, Self>) {
+        self.object = object;
+    }
+}
+
+impl Rc for SetCookieCallbackBuilder {
+    fn as_base(&self) -> &sys::cef_base_ref_counted_t {
+        unsafe {
+            let base = &*self.object;
+            std::mem::transmute(&base.cef_object)
+        }
+    }
+}
+
+impl Clone for SetCookieCallbackBuilder {
+    fn clone(&self) -> Self {
+        let object = unsafe {
+            let rc_impl = &mut *self.object;
+            rc_impl.interface.add_ref();
+            rc_impl
+        };
+
+        Self {
+            object,
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl ImplSetCookieCallback for SetCookieCallbackBuilder {
+    fn get_raw(&self) -> *mut sys::_cef_set_cookie_callback_t {
+        self.object.cast()
+    }
+
+    fn on_complete(&self, success: ::std::os::raw::c_int) {
+        if let Some(tx) = self.tx.lock().ok().and_then(|mut tx| tx.take()) {
+            _ = tx.send(success == 1);
+        }
+    }
+}
+
+struct DeleteCookiesCallbackBuilder {
+    object: *mut RcImpl<sys::_cef_delete_cookies_callback_t, Self>,
+    tx: Arc<Mutex<Option<oneshot::Sender<usize>>>>,
+}
+
+impl DeleteCookiesCallbackBuilder {
+    fn build(tx: oneshot::Sender<usize>) -> DeleteCookiesCallback {
+        DeleteCookiesCallback::new(Self {
+            object: null_mut(),
+            tx: Arc::new(Mutex::new(Some(tx))),
+        })
+    }
+}
+
+impl WrapDeleteCookiesCallback for DeleteCookiesCallbackBuilder {
+    fn wrap_rc(&mut self, object: *mut RcImpl<sys::_cef_delete_cookies_callback_t, Self>) {
+        self.object = object;
+    }
+}
+
+impl Rc for DeleteCookiesCallbackBuilder {
+    fn as_base(&self) -> &sys::cef_base_ref_counted_t {
+        unsafe {
+            let base = &*self.object;
+            std::mem::transmute(&base.cef_object)
+        }
+    }
+}
+
+impl Clone for DeleteCookiesCallbackBuilder {
+    fn clone(&self) -> Self {
+        let object = unsafe {
+            let rc_impl = &mut *self.object;
+            rc_impl.interface.add_ref();
+            rc_impl
+        };
+
+        Self {
+            object,
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl ImplDeleteCookiesCallback for DeleteCookiesCallbackBuilder {
+    fn get_raw(&self) -> *mut sys::_cef_delete_cookies_callback_t {
+        self.object.cast()
+    }
+
+    fn on_complete(&self, num_deleted: ::std::os::raw::c_int) {
+        if let Some(tx) = self.tx.lock().ok().and_then(|mut tx| tx.take()) {
+            _ = tx.send(num_deleted.max(0) as _);
+        }
+    }
+}
+
+struct CompletionCallbackBuilder {
+    object: *mut RcImpl<sys::_cef_completion_callback_t, Self>,
+    tx: Arc<Mutex<Option<oneshot::Sender<()>>>>,
+}
+
+impl CompletionCallbackBuilder {
+    fn build(tx: oneshot::Sender<()>) -> CompletionCallback {
+        CompletionCallback::new(Self {
+            object: null_mut(),
+            tx: Arc::new(Mutex::new(Some(tx))),
+        })
+    }
+}
+
+impl WrapCompletionCallback for CompletionCallbackBuilder {
+    fn wrap_rc(&mut self, object: *mut RcImpl<sys::_cef_completion_callback_t, Self>) {
+        self.object = object;
+    }
+}
+
+impl Rc for CompletionCallbackBuilder {
+    fn as_base(&self) -> &sys::cef_base_ref_counted_t {
+        unsafe {
+            let base = &*self.object;
+            std::mem::transmute(&base.cef_object)
+        }
+    }
+}
+
+impl Clone for CompletionCallbackBuilder {
+    fn clone(&self) -> Self {
+        let object = unsafe {
+            let rc_impl = &mut *self.object;
+            rc_impl.interface.add_ref();
+            rc_impl
+        };
+
+        Self {
+            object,
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl ImplCompletionCallback for CompletionCallbackBuilder {
+    fn get_raw(&self) -> *mut sys::_cef_completion_callback_t {
+        self.object.cast()
+    }
+
+    fn on_complete(&self) {
+        if let Some(tx) = self.tx.lock().ok().and_then(|mut tx| tx.take()) {
+            _ = tx.send(());
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Calling DevTools protocol methods of a browser and awaiting their result
+
+use cef::{
+    self, DevToolsMessageObserver, ImplBrowser, ImplBrowserHost, ImplDevToolsMessageObserver,
+    WrapDevToolsMessageObserver,
+    rc::{Rc, RcImpl},
+    sys,
+};
+use std::{cell::RefCell, collections::BTreeMap, ptr::null_mut};
+
+/// Continuation run on the ui thread with the json result or the error message of a method
+type DevToolsCallback = Box<dyn FnOnce(&cef::BrowserHost, Result<serde_json::Value, String>)>;
+
+type PendingMethods = std::rc::Rc<RefCell<BTreeMap<i32, DevToolsCallback>>>;
+
+#[derive(Clone, Default)]
+pub struct IcyDevToolsState {
+    next_id: std::rc::Rc<RefCell<i32>>,
+    pending: PendingMethods,
+    /// keeps the observer attached to the browser
+    registration: std::rc::Rc<RefCell<Option<cef::Registration>>>,
+}
+
+impl std::fmt::Debug for IcyDevToolsState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IcyDevToolsState")
+            .field("pending", &self.pending.borrow().keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl IcyDevToolsState {
+    /// Send `method` to the browser of `host`, `callback` runs once its result arrives
+    pub(crate) fn execute(
+        &self,
+        host: &cef::BrowserHost,
+        method: &str,
+        params: serde_json::Value,
+        callback: impl FnOnce(&cef::BrowserHost, Result<serde_json::Value, String>) + 'static,
+    ) {
+        if self.registration.borrow().is_none() {
+            let mut observer = DevToolsMessageObserverBuilder::build(self.pending.clone());
+            *self.registration.borrow_mut() =
+                host.add_dev_tools_message_observer(Some(&mut observer));
+        }
+        let id = {
+            let mut next_id = self.next_id.borrow_mut();
+            *next_id = next_id.wrapping_add(1);
+            *next_id
+        };
+        let message = serde_json::json!({
+            "id": id,
+            "method": method,
+            "params": params,
+        })
+        .to_string();
+        self.pending.borrow_mut().insert(id, Box::new(callback));
+        if host.send_dev_tools_message(message.as_ptr(), message.len()) != 1 {
+            resolve(
+                &self.pending,
+                host,
+                id,
+                Err(format!("cannot send devtools method {method}")),
+            );
+        }
+    }
+}
+
+fn resolve(
+    pending: &PendingMethods,
+    host: &cef::BrowserHost,
+    id: i32,
+    result: Result<serde_json::Value, String>,
+) {
+    let Some(callback) = pending.borrow_mut().remove(&id) else {
+        return;
+    };
+    callback(host, result);
+}
+
+struct DevToolsMessageObserverBuilder {
+    object: *mut RcImpl<sys::_cef_dev_tools_message_observer_t, Self>,
+    pending: PendingMethods,
+}
+
+impl DevToolsMessageObserverBuilder {
+    fn build(pending: PendingMethods) -> DevToolsMessageObserver {
+        DevToolsMessageObserver::new(Self {
+            object: null_mut(),
+            pending,
+        })
+    }
+}
+
+impl WrapDevToolsMessageObserver for DevToolsMessageObserverBuilder {
+    fn wrap_rc(&mut self, object: *mut RcImpl<sys::_cef_dev_tools_message_observer_t, Self>) {
+        self.object = object;
+    }
+}
+
+impl Rc for DevToolsMessageObserverBuilder {
+    fn as_base(&self) -> &sys::cef_base_ref_counted_t {
+        unsafe {
+            let base = &*self.object;
+            std::mem::transmute(&base.cef_object)
+        }
+    }
+}
+
+impl Clone for DevToolsMessageObserverBuilder {
+    fn clone(&self) -> Self {
+        let object = unsafe {
+            let rc_impl = &mut *self.object;
+            rc_impl.interface.add_ref();
+            rc_impl
+        };
+
+        Self {
+            object,
+            pending: self.pending.clone(),
+        }
+    }
+}
+
+impl ImplDevToolsMessageObserver for DevToolsMessageObserverBuilder {
+    fn get_raw(&self) -> *mut sys::_cef_dev_tools_message_observer_t {
+        self.object.cast()
+    }
+
+    fn on_dev_tools_method_result(
+        &self,
+        browser: Option<&mut cef::Browser>,
+        message_id: ::std::os::raw::c_int,
+        success: ::std::os::raw::c_int,
+        result: *const u8,
+        result_size: usize,
+    ) {
+        let Some(host) = browser.and_then(|browser| browser.host()) else {
+            return;
+        };
+        let payload = if result.is_null() {
+            &[][..]
+        } else {
+            unsafe { std::slice::from_raw_parts(result, result_size) }
+        };
+        let value =
+            serde_json::from_slice::<serde_json::Value>(payload).map_err(|err| err.to_string());
+        let result = match (success == 1, value) {
+            (true, value) => value,
+            // failures carry `{ "code", "message" }`
+            (false, Ok(error)) => Err(error
+                .get("message")
+                .and_then(|message| message.as_str())
+                .unwrap_or("devtools method failed")
+                .to_string()),
+            (false, Err(err)) => Err(err),
+        };
+        resolve(&self.pending, &host, message_id, result);
+    }
+}
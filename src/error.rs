@@ -25,3 +25,62 @@ pub enum CefError {
     #[error("custom: {0}")]
     Custom(&'static str),
 }
+
+#[derive(Error, Debug, Clone)]
+pub enum EvalError {
+    #[error("javascript exception: {message}")]
+    Exception {
+        message: String,
+        stack: Option<String>,
+    },
+    #[error("no browser to evaluate script in")]
+    NoBrowser,
+    #[error("cannot send script to render process")]
+    SendFailed,
+    #[error("evaluation cancelled")]
+    Cancelled,
+    #[error("invalid evaluation result: {0}")]
+    InvalidResult(String),
+}
+
+#[derive(Error, Debug, Clone)]
+pub enum CookieError {
+    #[error("cookie operation failed")]
+    Failed,
+    #[error("cookie operation cancelled")]
+    Cancelled,
+}
+
+#[derive(Error, Debug, Clone)]
+pub enum ProfileError {
+    #[error("invalid profile name {0:?}, it must be a single path component")]
+    InvalidName(String),
+    #[error("no root cache path to persist the profile in")]
+    NoRootCachePath,
+}
+
+#[derive(Error, Debug, Clone)]
+pub enum PdfError {
+    #[error("no browser to print")]
+    NoBrowser,
+    #[error("cannot print to pdf")]
+    Failed,
+    #[error("printing cancelled")]
+    Cancelled,
+}
+
+#[derive(Error, Debug, Clone)]
+pub enum ScreenshotError {
+    #[error("no browser to capture")]
+    NoBrowser,
+    #[error("no frame has been painted yet")]
+    NoFrame,
+    #[error("cannot read back the frame: {0}")]
+    Readback(String),
+    #[error("devtools capture failed: {0}")]
+    DevTools(String),
+    #[error("cannot decode screenshot: {0}")]
+    Decode(String),
+    #[error("capture cancelled")]
+    Cancelled,
+}
@@ -0,0 +1,106 @@
+//! Evaluating javascript in the render process and awaiting its result
+
+use cef::{ImplFrame, ImplListValue};
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    sync::atomic::{AtomicI32, Ordering},
+};
+use tokio::sync::oneshot;
+
+use crate::error::EvalError;
+
+pub(crate) const EVAL_REQUEST: &str = "browser.eval";
+pub(crate) const EVAL_RESULT: &str = "renderer.eval_result";
+
+type EvalSender = oneshot::Sender<Result<serde_json::Value, EvalError>>;
+
+#[derive(Clone, Debug, Default)]
+pub struct IcyEvalState {
+    pending: std::rc::Rc<RefCell<BTreeMap<i32, EvalSender>>>,
+}
+
+impl IcyEvalState {
+    fn register(&self) -> (i32, oneshot::Receiver<Result<serde_json::Value, EvalError>>) {
+        static NEXT_EVAL_ID: AtomicI32 = AtomicI32::new(0);
+        let id = NEXT_EVAL_ID.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.pending.borrow_mut().insert(id, tx);
+        (id, rx)
+    }
+
+    pub(crate) fn resolve(&self, id: i32, payload: &str) {
+        let Some(tx) = self.pending.borrow_mut().remove(&id) else {
+            tracing::warn!(id, "no pending evaluation");
+            return;
+        };
+        _ = tx.send(parse_result(payload));
+    }
+
+    pub(crate) fn send(
+        &self,
+        frame: &cef::Frame,
+        script: &str,
+    ) -> Result<oneshot::Receiver<Result<serde_json::Value, EvalError>>, EvalError> {
+        let Some(mut message) = cef::process_message_create(Some(&EVAL_REQUEST.into())) else {
+            return Err(EvalError::SendFailed);
+        };
+        let Some(args) = message.argument_list() else {
+            return Err(EvalError::SendFailed);
+        };
+        let (id, rx) = self.register();
+        let code = wrap_script(script);
+        if args.set_int(0, id) != 1 || args.set_string(1, Some(&code.as_str().into())) != 1 {
+            self.pending.borrow_mut().remove(&id);
+            return Err(EvalError::SendFailed);
+        }
+        frame.send_process_message(
+            cef::sys::cef_process_id_t::PID_RENDERER.into(),
+            Some(&mut message),
+        );
+        Ok(rx)
+    }
+}
+
+/// Wrap the script so that the render process always gets back a promise of a json string,
+/// either `{ "ok": value }` or `{ "error": { "message", "stack" } }`. Returned promises are
+/// awaited.
+fn wrap_script(script: &str) -> String {
+    let script = serde_json::to_string(script).unwrap_or_default();
+    format!(
+        r#"(async () => {{
+  try {{
+    const value = await (0, eval)({script});
+    return JSON.stringify({{ ok: value === undefined ? null : value }});
+  }} catch (error) {{
+    return JSON.stringify({{
+      error: {{
+        message: String(error && error.message !== undefined ? error.message : error),
+        stack: error && error.stack ? String(error.stack) : null,
+      }},
+    }});
+  }}
+}})()"#
+    )
+}
+
+fn parse_result(payload: &str) -> Result<serde_json::Value, EvalError> {
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Outcome {
+        Ok(serde_json::Value),
+        Error {
+            message: String,
+            stack: Option<String>,
+        },
+        /// The context went away before the script finished, e.g. by navigation
+        Cancelled,
+    }
+
+    match serde_json::from_str(payload) {
+        Ok(Outcome::Ok(value)) => Ok(value),
+        Ok(Outcome::Cancelled) => Err(EvalError::Cancelled),
+        Ok(Outcome::Error { message, stack }) => Err(EvalError::Exception { message, stack }),
+        Err(err) => Err(EvalError::InvalidResult(err.to_string())),
+    }
+}
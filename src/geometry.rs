@@ -0,0 +1,203 @@
+//! Conversions between the coordinate spaces of a webview
+//!
+//! - device: physical pixels of the rendered frame, as reported by the render process
+//! - view: logical pixels relative to the top left corner of the webview
+//! - window: logical pixels of the iced window, the view shifted by the widget bounds
+
+use iced::{Point, Rectangle, Size};
+
+/// Scale factors cef cannot work with fall back to 1x
+fn sanitize(device_scale_factor: f32) -> f32 {
+    if device_scale_factor.is_finite() && device_scale_factor > 0.0 {
+        device_scale_factor
+    } else {
+        1.0
+    }
+}
+
+/// Physical pixels of the frame to logical pixels of the view
+pub(crate) fn device_to_view(rect: Rectangle, device_scale_factor: f32) -> Rectangle {
+    let scale = sanitize(device_scale_factor);
+    Rectangle {
+        x: rect.x / scale,
+        y: rect.y / scale,
+        width: rect.width / scale,
+        height: rect.height / scale,
+    }
+}
+
+/// Logical pixels of the view to physical pixels of the frame, snapped outwards to whole
+/// pixels so the result covers all of `rect`
+pub(crate) fn view_to_device(rect: Rectangle, device_scale_factor: f32) -> Rectangle<u32> {
+    let scale = sanitize(device_scale_factor);
+    let left = (rect.x * scale).floor().max(0.0);
+    let top = (rect.y * scale).floor().max(0.0);
+    let right = ((rect.x + rect.width) * scale).ceil().max(left);
+    let bottom = ((rect.y + rect.height) * scale).ceil().max(top);
+    Rectangle {
+        x: left as _,
+        y: top as _,
+        width: (right - left) as _,
+        height: (bottom - top) as _,
+    }
+}
+
+/// Place a rectangle of the view into the window at the widget `bounds`
+pub(crate) fn view_to_window(rect: Rectangle, bounds: Rectangle) -> Rectangle {
+    Rectangle::new(
+        Point::new(rect.x + bounds.x, rect.y + bounds.y),
+        rect.size(),
+    )
+}
+
+/// Where the candidate window of an input method opens for a focused `node` of the view:
+/// below its bottom left corner, kept inside the widget `bounds`
+pub(crate) fn ime_position(node: Rectangle, bounds: Rectangle) -> Point {
+    let node = view_to_window(node, bounds);
+    Point::new(
+        node.x.clamp(bounds.x, bounds.x + bounds.width),
+        (node.y + node.height).clamp(bounds.y, bounds.y + bounds.height),
+    )
+}
+
+/// An empty rectangle at `point`, for places where only a point is known
+pub(crate) fn point_rect(point: Point) -> Rectangle {
+    Rectangle::new(point, Size::ZERO)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCALES: [f32; 4] = [1.0, 1.25, 1.5, 2.0];
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> Rectangle {
+        Rectangle {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn device(x: u32, y: u32, width: u32, height: u32) -> Rectangle<u32> {
+        Rectangle {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn device_to_view_divides_by_scale() {
+        let cases = [
+            (1.0, rect(100.0, 50.0, 200.0, 30.0)),
+            (1.25, rect(80.0, 40.0, 160.0, 24.0)),
+            (1.5, rect(100.0 / 1.5, 50.0 / 1.5, 200.0 / 1.5, 20.0)),
+            (2.0, rect(50.0, 25.0, 100.0, 15.0)),
+        ];
+        for (scale, expected) in cases {
+            let view = device_to_view(rect(100.0, 50.0, 200.0, 30.0), scale);
+            assert_eq!(view, expected, "scale {scale}");
+        }
+    }
+
+    #[test]
+    fn device_to_view_keeps_width_and_height_apart() {
+        for scale in SCALES {
+            let view = device_to_view(rect(0.0, 0.0, 300.0, 20.0), scale);
+            assert!(view.width > view.height, "scale {scale}: {view:?}");
+        }
+    }
+
+    #[test]
+    fn invalid_scale_falls_back_to_1x() {
+        let painted = rect(10.0, 20.0, 30.0, 40.0);
+        for scale in [0.0, -2.0, f32::NAN, f32::INFINITY] {
+            assert_eq!(device_to_view(painted, scale), painted, "scale {scale}");
+        }
+    }
+
+    #[test]
+    fn view_to_device_covers_the_view_rect() {
+        let cases = [
+            (1.0, device(10, 10, 20, 10)),
+            (1.25, device(12, 12, 26, 13)),
+            (1.5, device(15, 15, 30, 15)),
+            (2.0, device(20, 20, 40, 20)),
+        ];
+        for (scale, expected) in cases {
+            let snapped = view_to_device(rect(10.0, 10.0, 20.0, 10.0), scale);
+            assert_eq!(snapped, expected, "scale {scale}");
+        }
+    }
+
+    #[test]
+    fn view_to_device_clamps_negative_origin() {
+        let snapped = view_to_device(rect(-5.0, -5.0, 10.0, 10.0), 2.0);
+        assert_eq!(snapped, device(0, 0, 10, 10));
+    }
+
+    #[test]
+    fn round_trip_stays_within_a_device_pixel() {
+        let view = rect(33.3, 17.7, 120.4, 18.2);
+        for scale in SCALES {
+            let snapped = view_to_device(view, scale);
+            let back = device_to_view(
+                rect(
+                    snapped.x as _,
+                    snapped.y as _,
+                    snapped.width as _,
+                    snapped.height as _,
+                ),
+                scale,
+            );
+            let pixel = 1.0 / scale;
+            assert!(back.x <= view.x && view.x - back.x < pixel, "scale {scale}");
+            assert!(back.y <= view.y && view.y - back.y < pixel, "scale {scale}");
+            assert!(back.x + back.width >= view.x + view.width, "scale {scale}");
+            assert!(
+                back.y + back.height >= view.y + view.height,
+                "scale {scale}"
+            );
+        }
+    }
+
+    #[test]
+    fn view_to_window_offsets_by_bounds() {
+        let bounds = rect(200.0, 40.0, 600.0, 400.0);
+        assert_eq!(
+            view_to_window(rect(10.0, 20.0, 30.0, 40.0), bounds),
+            rect(210.0, 60.0, 30.0, 40.0)
+        );
+    }
+
+    #[test]
+    fn ime_position_is_below_the_node() {
+        let bounds = rect(200.0, 40.0, 600.0, 400.0);
+        for scale in SCALES {
+            // an input of 300x24 logical pixels at (50, 100) of the page
+            let painted = rect(50.0 * scale, 100.0 * scale, 300.0 * scale, 24.0 * scale);
+            let node = device_to_view(painted, scale);
+            let position = ime_position(node, bounds);
+            assert!(
+                (position.x - 250.0).abs() < 1e-3,
+                "scale {scale}: {position:?}"
+            );
+            assert!(
+                (position.y - 164.0).abs() < 1e-3,
+                "scale {scale}: {position:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn ime_position_stays_inside_bounds() {
+        let bounds = rect(0.0, 0.0, 400.0, 300.0);
+        let position = ime_position(rect(350.0, 290.0, 100.0, 30.0), bounds);
+        assert_eq!(position, Point::new(350.0, 300.0));
+        let position = ime_position(rect(-20.0, -50.0, 10.0, 10.0), bounds);
+        assert_eq!(position, Point::new(0.0, 0.0));
+    }
+}
@@ -1,21 +1,16 @@
+use crate::client::{ClientBuilder, IcyClient, IcyClientState, LifeSpanEvent, LoadEvent};
 use crate::{
-    BrowserId, Webview,
-    client::{CefFrame, ClientEventSubscriber},
-};
-use crate::{
-    client::{ClientBuilder, IcyClient, IcyClientState, LifeSpanEvent, LoadEvent},
-    request::{IcyRequestContextHandler, RequestContextHandlerBuilder},
+    BrowserId, ContextMenu, ContextMenuEvent, ContextMenuItem, ContextMenuParams, CookieManager,
+    DownloadCommand, DownloadEvent, DownloadPolicy, EvalError, FileDialogProvider,
+    FileDialogRequest, FindResult, JsBindings, JsDialog, JsDialogEvent, MenuCommand, PdfError,
+    PdfOptions, PopupAction, PopupRequest, PopupWebview, Profile, RequestInterceptor, Screenshot,
+    ScreenshotArea, ScreenshotError, Webview,
+    client::{CefFrame, ClientEventSubscriber, DisplayEvent, WebviewOptions},
+    key_event, zoom,
 };
 use cef;
 use cef::*;
-use iced::{
-    Element, Subscription, Task,
-    keyboard::{
-        Key,
-        key::{Code, Named, Physical},
-    },
-    window,
-};
+use iced::{Element, Subscription, Task, window};
 use iced_core::mouse::Click;
 use std::{
     cell::RefCell, collections::BTreeMap, fmt::Debug, sync::atomic::AtomicUsize, time::Duration,
@@ -24,24 +19,79 @@ use tokio_stream::{StreamExt, wrappers::UnboundedReceiverStream};
 
 pub enum CefAction {
     Loaded(BrowserId),
+    Load(LoadEvent),
+    Display(DisplayEvent),
     Run(Task<CefMessage>),
     Created(BrowserId),
     Closed(BrowserId),
+    PopupRequested(PopupRequest),
+    PopupOpened(PopupRequest, PopupWebview),
+    ContextMenu(ContextMenuEvent),
+    Download(DownloadEvent),
+    /// Sent unless a [`CefComponent::file_dialog_provider`] answers the file chooser,
+    /// the page waits for [`CefMessage::FileDialogResponse`]
+    FileDialog(FileDialogRequest),
+    JsDialog(JsDialogEvent),
+    Find(FindResult),
+    /// The zoom factor of the page changed
+    ZoomChanged(BrowserId, f64),
     None,
 }
 
 #[derive(Clone)]
 pub enum CefMessage {
     Loaded(BrowserId),
+    Load(LoadEvent),
+    Display(DisplayEvent),
     Create(window::Id, url::Url, iced::Point, iced::Size, f32),
     Created(BrowserId),
     Closed(BrowserId),
+    PopupRequested(PopupRequest),
+    PopupOpened(PopupRequest, PopupWebview),
+    ContextMenu(ContextMenuEvent),
+    /// Answer the open context menu, `None` dismisses it
+    ContextMenuCommand(Option<MenuCommand>),
+    Download(DownloadEvent),
+    /// Control the download with this id
+    DownloadCommand(u32, DownloadCommand),
+    FileDialog(FileDialogRequest),
+    /// Answer the file chooser with this id, `None` cancels it
+    FileDialogResponse(u32, Option<Vec<camino::Utf8PathBuf>>),
+    JsDialog(JsDialogEvent),
+    /// Text typed into the prompt of the built-in dialog overlay
+    JsDialogInput(String),
+    /// Close the javascript dialog with this id, the text is only used by prompts
+    JsDialogResponse(u32, bool, Option<String>),
+    FindResult(FindResult),
+    /// Start searching the page for the text, the flag enables case sensitivity
+    Find(String, bool),
+    /// Move to the next match of the current search
+    FindNext,
+    /// Move to the previous match of the current search
+    FindPrevious,
+    /// End the current search, the flag also clears the selected match
+    StopFinding(bool),
+    ZoomIn,
+    ZoomOut,
+    ZoomReset,
+    /// Set the zoom factor, `1.0` is the default size
+    SetZoom(f64),
+    /// Apply the zoom remembered by the profile for the host of this url
+    RestoreZoom(String),
     UpdateCaretOffset(BrowserId, f32),
     FocusedNodeChanged(BrowserId, iced::Rectangle),
+    /// Give or take the keyboard focus, sent by the webview when clicked into or outside
+    Focus(bool),
     KeyEvent(iced::keyboard::Event),
     MouseEvent(iced::Point, iced::mouse::Event),
     InputMethodEvent(iced_core::input_method::Event),
     UpdateView(CefFrame),
+    LoadUrl(url::Url),
+    GoBack,
+    GoForward,
+    Reload,
+    ReloadIgnoreCache,
+    StopLoad,
 }
 
 impl std::fmt::Debug for CefMessage {
@@ -56,8 +106,56 @@ impl std::fmt::Debug for CefMessage {
                 .field(device_scale_factor)
                 .finish(),
             Self::Loaded(browser_id) => f.debug_tuple("Loaded").field(browser_id).finish(),
+            Self::Load(event) => f.debug_tuple("Load").field(event).finish(),
+            Self::Display(event) => f.debug_tuple("Display").field(event).finish(),
             Self::Created(browser_id) => f.debug_tuple("Created").field(browser_id).finish(),
             Self::Closed(browser_id) => f.debug_tuple("Closed").field(browser_id).finish(),
+            Self::PopupRequested(request) => {
+                f.debug_tuple("PopupRequested").field(request).finish()
+            }
+            Self::PopupOpened(request, popup) => f
+                .debug_tuple("PopupOpened")
+                .field(request)
+                .field(popup)
+                .finish(),
+            Self::ContextMenu(event) => f.debug_tuple("ContextMenu").field(event).finish(),
+            Self::ContextMenuCommand(command) => {
+                f.debug_tuple("ContextMenuCommand").field(command).finish()
+            }
+            Self::Download(event) => f.debug_tuple("Download").field(event).finish(),
+            Self::DownloadCommand(id, command) => f
+                .debug_tuple("DownloadCommand")
+                .field(id)
+                .field(command)
+                .finish(),
+            Self::FileDialog(request) => f.debug_tuple("FileDialog").field(request).finish(),
+            Self::FileDialogResponse(id, paths) => f
+                .debug_tuple("FileDialogResponse")
+                .field(id)
+                .field(paths)
+                .finish(),
+            Self::JsDialog(event) => f.debug_tuple("JsDialog").field(event).finish(),
+            Self::JsDialogInput(input) => f.debug_tuple("JsDialogInput").field(input).finish(),
+            Self::JsDialogResponse(id, accepted, input) => f
+                .debug_tuple("JsDialogResponse")
+                .field(id)
+                .field(accepted)
+                .field(input)
+                .finish(),
+            Self::FindResult(result) => f.debug_tuple("FindResult").field(result).finish(),
+            Self::Find(text, match_case) => {
+                f.debug_tuple("Find").field(text).field(match_case).finish()
+            }
+            Self::ZoomIn => f.debug_tuple("ZoomIn").finish(),
+            Self::ZoomOut => f.debug_tuple("ZoomOut").finish(),
+            Self::ZoomReset => f.debug_tuple("ZoomReset").finish(),
+            Self::SetZoom(factor) => f.debug_tuple("SetZoom").field(factor).finish(),
+            Self::RestoreZoom(url) => f.debug_tuple("RestoreZoom").field(url).finish(),
+            Self::FindNext => f.debug_tuple("FindNext").finish(),
+            Self::FindPrevious => f.debug_tuple("FindPrevious").finish(),
+            Self::StopFinding(clear_selection) => {
+                f.debug_tuple("StopFinding").field(clear_selection).finish()
+            }
             Self::UpdateView(browser_id) => f.debug_tuple("UpdateView").field(browser_id).finish(),
             Self::UpdateCaretOffset(browser_id, offset) => f
                 .debug_tuple("UpdateCaretOffset")
@@ -77,7 +175,14 @@ impl std::fmt::Debug for CefMessage {
             Self::InputMethodEvent(event) => {
                 f.debug_tuple("InputMethodEvent").field(event).finish()
             }
+            Self::Focus(focused) => f.debug_tuple("Focus").field(focused).finish(),
             Self::KeyEvent(event) => f.debug_tuple("KeyEvent").field(event).finish(),
+            Self::LoadUrl(url) => f.debug_tuple("LoadUrl").field(url).finish(),
+            Self::GoBack => f.debug_tuple("GoBack").finish(),
+            Self::GoForward => f.debug_tuple("GoForward").finish(),
+            Self::Reload => f.debug_tuple("Reload").finish(),
+            Self::ReloadIgnoreCache => f.debug_tuple("ReloadIgnoreCache").finish(),
+            Self::StopLoad => f.debug_tuple("StopLoad").finish(),
         }
     }
 }
@@ -87,8 +192,28 @@ impl std::fmt::Debug for CefAction {
         match self {
             CefAction::Run(_) => f.debug_tuple("Run").finish(),
             CefAction::Loaded(browser_id) => f.debug_tuple("Loaded").field(browser_id).finish(),
+            CefAction::Load(event) => f.debug_tuple("Load").field(event).finish(),
+            CefAction::Display(event) => f.debug_tuple("Display").field(event).finish(),
             CefAction::Created(browser_id) => f.debug_tuple("Created").field(browser_id).finish(),
             CefAction::Closed(browser_id) => f.debug_tuple("Closed").field(browser_id).finish(),
+            CefAction::PopupRequested(request) => {
+                f.debug_tuple("PopupRequested").field(request).finish()
+            }
+            CefAction::PopupOpened(request, popup) => f
+                .debug_tuple("PopupOpened")
+                .field(request)
+                .field(popup)
+                .finish(),
+            CefAction::ContextMenu(event) => f.debug_tuple("ContextMenu").field(event).finish(),
+            CefAction::Download(event) => f.debug_tuple("Download").field(event).finish(),
+            CefAction::FileDialog(request) => f.debug_tuple("FileDialog").field(request).finish(),
+            CefAction::JsDialog(event) => f.debug_tuple("JsDialog").field(event).finish(),
+            CefAction::Find(result) => f.debug_tuple("Find").field(result).finish(),
+            CefAction::ZoomChanged(browser_id, factor) => f
+                .debug_tuple("ZoomChanged")
+                .field(browser_id)
+                .field(factor)
+                .finish(),
             CefAction::None => f.debug_tuple("None").finish(),
         }
     }
@@ -122,48 +247,46 @@ pub(crate) fn new_webview(launch_id: LaunchId, browser_id: BrowserId) {
     }
 }
 
-pub(crate) fn remove_webview(browser_id: BrowserId) {
-    WEBVIEW_STATES.with_borrow_mut(|states| {
-        states.remove(&browser_id);
+/// Drop the state of a browser whose creation was cancelled before `on_after_created`
+pub(crate) fn abort_browser(launch_id: LaunchId) {
+    LAUNCHED_BROWSERS.with_borrow_mut(|browsers| {
+        browsers.remove(&launch_id);
     });
 }
 
-pub(crate) fn get_cursor_type(browser_id: BrowserId) -> Option<cef::CursorType> {
-    WEBVIEW_STATES.with_borrow(|states| {
-        states
-            .get(&browser_id)
-            .map(|state| state.display.cursor_type.borrow().clone())
-    })
-}
-
-pub(crate) fn resize(browser_id: BrowserId, bound: iced::Rectangle) {
+pub(crate) fn remove_webview(browser_id: BrowserId) {
     WEBVIEW_STATES.with_borrow_mut(|states| {
-        if let Some(state) = states.get(&browser_id)
-            && bound.width > 0.0
-            && bound.height > 0.0
-        {
-            state.render.set_view_rect(cef::Rect {
-                x: bound.x as i32,
-                y: bound.y as i32,
-                width: bound.width as i32,
-                height: bound.height as i32,
-            });
-            if let Some(host) =
-                browser_host_get_browser_by_identifier(browser_id.inner()).and_then(|b| b.host())
-            {
-                host.was_resized();
-            }
-        }
-    })
+        states.remove(&browser_id);
+    });
 }
 
 pub struct CefComponent {
     view: Option<CefFrame>,
     host: Option<BrowserHost>,
+    /// handler state of the browser shown by this component
+    client: Option<IcyClientState>,
     focused_node: Option<iced::Rectangle>,
     caret_offset: Option<f32>,
     last_click: Option<Click>,
     last_button_modifiers: u32,
+    is_loading: bool,
+    can_go_back: bool,
+    can_go_forward: bool,
+    opener: Option<BrowserId>,
+    context_menu: Option<ContextMenu>,
+    context_menu_overlay: bool,
+    js_dialog: Option<JsDialog>,
+    js_dialog_input: String,
+    js_dialog_overlay: bool,
+    js_dialog_style: Option<std::rc::Rc<dyn Fn(&iced::Theme) -> iced::widget::container::Style>>,
+    find_query: Option<(String, bool)>,
+    find_result: Option<FindResult>,
+    zoom_factor: f64,
+    /// host the zoom factor is remembered for
+    zoom_host: Option<String>,
+    modifiers: iced::keyboard::Modifiers,
+    focused: bool,
+    options: WebviewOptions,
 }
 
 impl CefComponent {
@@ -175,6 +298,229 @@ impl CefComponent {
             last_click: None,
             last_button_modifiers: 0,
             host: None,
+            client: None,
+            is_loading: false,
+            can_go_back: false,
+            can_go_forward: false,
+            opener: None,
+            context_menu: None,
+            context_menu_overlay: true,
+            js_dialog: None,
+            js_dialog_input: String::new(),
+            js_dialog_overlay: false,
+            js_dialog_style: None,
+            find_query: None,
+            find_result: None,
+            zoom_factor: 1.0,
+            zoom_host: None,
+            modifiers: iced::keyboard::Modifiers::default(),
+            focused: true,
+            options: WebviewOptions::default(),
+        }
+    }
+
+    /// Attach to a popup opened by another webview, see [`CefAction::PopupOpened`].
+    /// The popup browser keeps the bindings, request interceptor, policies and providers
+    /// of its opener, overlays and styles of the returned component start from the defaults.
+    pub fn from_popup(popup: PopupWebview) -> (Self, Task<CefMessage>) {
+        let mut component = Self::new();
+        component.opener = Some(popup.opener);
+        let task = match popup.subscribers.take() {
+            Some(subscribers) => Self::subscribe(subscribers),
+            None => {
+                tracing::warn!(?popup, "popup is already attached");
+                Task::none()
+            }
+        };
+        (component, task)
+    }
+
+    /// Decide what happens to `window.open` and `target=_blank` links of webviews created
+    /// afterwards, popups are denied by default.
+    pub fn on_popup(mut self, policy: impl Fn(&PopupRequest) -> PopupAction + 'static) -> Self {
+        self.options.popup_policy = Some(std::rc::Rc::new(policy));
+        self
+    }
+
+    /// Rust functions exposed to the pages of webviews created afterwards.
+    pub fn bindings(mut self, bindings: JsBindings) -> Self {
+        self.options.bindings = bindings;
+        self
+    }
+
+    /// Inspect and rewrite every resource request of webviews created afterwards.
+    pub fn request_interceptor(mut self, interceptor: impl RequestInterceptor) -> Self {
+        self.options.request_interceptor = Some(std::sync::Arc::new(interceptor));
+        self
+    }
+
+    /// Storage shared by webviews created afterwards, a fresh in-memory context if unset.
+    pub fn profile(mut self, profile: Profile) -> Self {
+        self.options.profile = Some(profile);
+        self
+    }
+
+    /// Customize the items of context menus of webviews created afterwards,
+    /// custom items are reported with [`ContextMenuEvent::Command`].
+    pub fn context_menu(
+        mut self,
+        builder: impl Fn(&ContextMenuParams, &mut Vec<ContextMenuItem>) + 'static,
+    ) -> Self {
+        self.options.context_menu = Some(std::rc::Rc::new(builder));
+        self
+    }
+
+    /// Render open context menus with [`crate::overlay::context_menu`], on by default.
+    /// Turn it off to render [`CefComponent::open_context_menu`] yourself.
+    pub fn context_menu_overlay(mut self, enabled: bool) -> Self {
+        self.context_menu_overlay = enabled;
+        self
+    }
+
+    /// Where downloads of webviews created afterwards go, see [`DownloadPolicy`].
+    pub fn download_policy(mut self, policy: DownloadPolicy) -> Self {
+        self.options.download_policy = policy;
+        self
+    }
+
+    /// Answer file choosers of webviews created afterwards without a round trip through
+    /// [`CefAction::FileDialog`], e.g. with a native picker or canned paths in tests.
+    pub fn file_dialog_provider(mut self, provider: impl FileDialogProvider) -> Self {
+        self.options.file_dialog = Some(std::rc::Rc::new(provider));
+        self
+    }
+
+    /// Forward file choosers of webviews created afterwards as [`CefAction::FileDialog`],
+    /// the default, drops a provider set before
+    pub fn defer_file_dialogs(mut self) -> Self {
+        self.options.file_dialog = None;
+        self
+    }
+
+    /// Answer `alert`, `confirm`, `prompt` and `onbeforeunload` of webviews created afterwards
+    /// with the modal [`crate::overlay::js_dialog`] instead of handling [`CefAction::JsDialog`]
+    /// yourself. Without the overlay or [`CefComponent::defer_js_dialogs`] dialogs are
+    /// suppressed and `onbeforeunload` leaves the page.
+    ///
+    /// An opening `prompt` returns [`CefAction::Run`] to focus its text field.
+    pub fn js_dialog_overlay(mut self, enabled: bool) -> Self {
+        self.js_dialog_overlay = enabled;
+        self.options.js_dialogs = enabled;
+        self
+    }
+
+    /// Forward javascript dialogs of webviews created afterwards as [`CefAction::JsDialog`],
+    /// answer them with [`CefMessage::JsDialogResponse`]
+    pub fn defer_js_dialogs(mut self) -> Self {
+        self.options.js_dialogs = true;
+        self
+    }
+
+    /// Style of the dialog box drawn by [`CefComponent::js_dialog_overlay`]
+    pub fn js_dialog_style(
+        mut self,
+        style: impl Fn(&iced::Theme) -> iced::widget::container::Style + 'static,
+    ) -> Self {
+        self.js_dialog_style = Some(std::rc::Rc::new(style));
+        self
+    }
+
+    /// Start with the keyboard focus, on by default. Turn it off for secondary webviews
+    /// sharing a window, they take the focus once clicked.
+    pub fn autofocus(mut self, enabled: bool) -> Self {
+        self.focused = enabled;
+        self
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Bounds of the focused element of the page in logical pixels of the view,
+    /// e.g. to draw a focus ring over the webview
+    pub fn focused_node(&self) -> Option<iced::Rectangle> {
+        self.focused_node
+    }
+
+    pub fn open_js_dialog(&self) -> Option<&JsDialog> {
+        self.js_dialog.as_ref()
+    }
+
+    /// Latest result of the running search
+    pub fn find_result(&self) -> Option<&FindResult> {
+        self.find_result.as_ref()
+    }
+
+    pub fn open_context_menu(&self) -> Option<&ContextMenu> {
+        self.context_menu.as_ref()
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.is_loading
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        self.can_go_back
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        self.can_go_forward
+    }
+
+    /// The webview which opened this one as a popup
+    pub fn opener(&self) -> Option<BrowserId> {
+        self.opener
+    }
+
+    fn browser(&self) -> Option<cef::Browser> {
+        self.host.as_ref().and_then(|host| host.browser())
+    }
+
+    pub fn zoom_factor(&self) -> f64 {
+        self.zoom_factor
+    }
+
+    fn zoom_shortcut(&mut self, shortcut: zoom::ZoomShortcut) -> CefAction {
+        let factor = match shortcut {
+            zoom::ZoomShortcut::In => zoom::zoom_in(self.zoom_factor),
+            zoom::ZoomShortcut::Out => zoom::zoom_out(self.zoom_factor),
+            zoom::ZoomShortcut::Reset => 1.0,
+        };
+        self.set_zoom(factor, true)
+    }
+
+    /// Apply `factor` to the page, `remember` stores it for the current host in the profile
+    fn set_zoom(&mut self, factor: f64, remember: bool) -> CefAction {
+        let factor = zoom::clamp_factor(factor);
+        let Some(browser) = self.browser() else {
+            return CefAction::None;
+        };
+        if let Some(host) = browser.host() {
+            host.set_zoom_level(zoom::factor_to_level(factor));
+        }
+        if remember
+            && let (Some(profile), Some(site)) = (self.options.profile.as_ref(), &self.zoom_host)
+        {
+            profile.set_zoom_for_host(site, factor);
+        }
+        if (self.zoom_factor - factor).abs() < f64::EPSILON {
+            return CefAction::None;
+        }
+        self.zoom_factor = factor;
+        CefAction::ZoomChanged(browser.identifier().into(), factor)
+    }
+
+    fn find_again(&self, forward: bool) {
+        if let (Some((text, match_case)), Some(host)) = (
+            self.find_query.as_ref(),
+            self.browser().and_then(|browser| browser.host()),
+        ) {
+            host.find(
+                Some(&text.as_str().into()),
+                forward as _,
+                *match_case as _,
+                true as _,
+            );
         }
     }
 
@@ -230,123 +576,48 @@ impl CefComponent {
         }
     }
 
-    fn send_key_event(&mut self, event: iced::keyboard::Event) {
+    fn send_key_event(&mut self, event: iced::keyboard::Event) -> CefAction {
         use iced::keyboard;
 
+        match &event {
+            keyboard::Event::ModifiersChanged(modifiers) => self.modifiers = *modifiers,
+            keyboard::Event::KeyPressed { key, modifiers, .. } => {
+                self.modifiers = *modifiers;
+                if let Some(shortcut) = zoom::shortcut(key, *modifiers) {
+                    return self.zoom_shortcut(shortcut);
+                }
+            }
+            keyboard::Event::KeyReleased { modifiers, .. } => self.modifiers = *modifiers,
+        }
+
         let Some(host) = &self.view.as_ref().and_then(|view| {
             cef::browser_host_get_browser_by_identifier(view.browser_id().inner())
                 .and_then(|b| b.host())
         }) else {
-            return;
-        };
-        let cef_event = match event {
-            iced::keyboard::Event::KeyPressed {
-                key,
-                modified_key,
-                physical_key,
-                modifiers,
-                text,
-                ..
-            } => iced_key_to_cef_key(
-                KeyPress::Press,
-                Some(modified_key),
-                Some(key),
-                physical_key.into(),
-                modifiers,
-                text,
-            ),
-            iced::keyboard::Event::KeyReleased {
-                key,
-                modified_key,
-                physical_key,
-                modifiers,
-                ..
-            } => iced_key_to_cef_key(
-                KeyPress::Unpress,
-                Some(modified_key),
-                Some(key),
-                physical_key.into(),
-                modifiers,
-                None,
-            ),
-            iced::keyboard::Event::ModifiersChanged(_) => None,
+            return CefAction::None;
         };
-        host.send_key_event(cef_event.as_ref());
-
-        #[derive(Debug, PartialEq, Eq)]
-        enum KeyPress {
-            Press,
-            Unpress,
+        for stroke in key_event::translate(&event, key_event::Platform::current()) {
+            host.send_key_event(Some(&stroke.to_cef()));
         }
 
-        fn iced_key_to_cef_key(
-            press: KeyPress,
-            modified_key: Option<keyboard::Key>,
-            key: Option<keyboard::Key>,
-            physical_key: Option<keyboard::key::Physical>,
-            modifiers: keyboard::Modifiers,
-            _text: Option<iced_core::SmolStr>,
-        ) -> Option<cef::KeyEvent> {
-            let mut modifiers_: cef::EventFlags =
-                cef::sys::cef_event_flags_t::EVENTFLAG_NONE.into();
-            let mut ty = match press {
-                KeyPress::Press => cef::sys::cef_key_event_type_t::KEYEVENT_KEYDOWN,
-                KeyPress::Unpress => cef::sys::cef_key_event_type_t::KEYEVENT_KEYUP,
-            };
-            if matches!(modified_key, Some(keyboard::Key::Character(_)))
-                && matches!(press, KeyPress::Press)
-            {
-                ty = cef::sys::cef_key_event_type_t::KEYEVENT_CHAR;
-            };
-
-            if modifiers.control() {
-                *modifiers_.as_mut() |= cef::sys::cef_event_flags_t::EVENTFLAG_CONTROL_DOWN;
-            } else if modifiers.alt() {
-                *modifiers_.as_mut() |= cef::sys::cef_event_flags_t::EVENTFLAG_ALT_DOWN;
-            } else if modifiers.shift() {
-                *modifiers_.as_mut() |= cef::sys::cef_event_flags_t::EVENTFLAG_SHIFT_DOWN;
-            } else if modifiers.logo() {
-                *modifiers_.as_mut() |= cef::sys::cef_event_flags_t::EVENTFLAG_COMMAND_DOWN;
-            }
-
-            let mut cef_keyevent = cef::KeyEvent::default();
-
-            cef_keyevent.type_ = ty.into();
-            cef_keyevent.windows_key_code = match &modified_key {
-                Some(keyboard::Key::Named(code)) => to_virtual_key(*code) as _,
-                Some(keyboard::Key::Character(c)) => c.chars().next().unwrap_or_default() as _,
-                _ => 0,
-            };
-
-            cef_keyevent.native_key_code = match &physical_key {
-                Some(Physical::Code(code)) => to_native_key(*code) as _,
-                _ => 0,
-            };
-            cef_keyevent.is_system_key = false as _;
-            cef_keyevent.focus_on_editable_field = false as _;
-            cef_keyevent.modifiers = modifiers_.as_ref().0;
-            cef_keyevent.character = match &modified_key {
-                Some(Key::Character(c)) => c.chars().next().unwrap_or_default() as _,
-                Some(Key::Named(named)) => named_key_to_text(*named).unwrap_or_default() as _,
-                _ => 0,
-            };
-            cef_keyevent.unmodified_character = match &key {
-                Some(Key::Character(c)) => c.chars().next().unwrap_or_default() as _,
-                Some(Key::Named(named)) => named_key_to_text(*named).unwrap_or_default() as _,
-                _ => 0,
-            };
-
-            Some(cef_keyevent)
-        }
+        CefAction::None
     }
 
-    fn send_mouse_event(&mut self, point: iced::Point, event: iced::mouse::Event) {
+    fn send_mouse_event(&mut self, point: iced::Point, event: iced::mouse::Event) -> CefAction {
         let Some(host) = &self.view.as_ref().and_then(|view| {
             cef::browser_host_get_browser_by_identifier(view.browser_id().inner())
                 .and_then(|b| b.host())
         }) else {
-            return;
+            return CefAction::None;
         };
+        // an open popup widget may be drawn shifted to fit into the view
+        let point = self
+            .view
+            .as_ref()
+            .client
+            .as_ref()
+            .map(|client| client.render.popup_point(point))
+            .unwrap_or(point);
         use iced::advanced::mouse::click::Kind;
         match event {
             iced::mouse::Event::ButtonPressed(button) => {
@@ -365,7 +636,7 @@ impl CefComponent {
                         cef::sys::cef_event_flags_t::EVENTFLAG_MIDDLE_MOUSE_BUTTON,
                         cef::sys::cef_mouse_button_type_t::MBT_MIDDLE,
                     ),
-                    _ => return,
+                    _ => return CefAction::None,
                 };
                 self.last_button_modifiers = modifier.0;
 
@@ -398,7 +669,7 @@ impl CefComponent {
                         cef::sys::cef_event_flags_t::EVENTFLAG_MIDDLE_MOUSE_BUTTON,
                         cef::sys::cef_mouse_button_type_t::MBT_MIDDLE,
                     ),
-                    _ => return,
+                    _ => return CefAction::None,
                 };
                 self.last_button_modifiers = 0;
                 let event = cef::MouseEvent {
@@ -408,6 +679,15 @@ impl CefComponent {
                 };
                 host.send_mouse_click_event(Some(&event), type_.into(), true as _, 1);
             }
+            iced::mouse::Event::WheelScrolled { delta } if self.modifiers.control() => {
+                let (iced::mouse::ScrollDelta::Lines { y, .. }
+                | iced::mouse::ScrollDelta::Pixels { y, .. }) = delta;
+                if y > 0.0 {
+                    return self.zoom_shortcut(zoom::ZoomShortcut::In);
+                } else if y < 0.0 {
+                    return self.zoom_shortcut(zoom::ZoomShortcut::Out);
+                }
+            }
             iced::mouse::Event::WheelScrolled { delta } => {
                 let event = cef::MouseEvent {
                     x: point.x as _,
@@ -445,10 +725,10 @@ impl CefComponent {
                 };
                 host.send_mouse_move_event(Some(&event), false as _);
             }
-            iced::mouse::Event::CursorMoved { position } => {
+            iced::mouse::Event::CursorMoved { .. } => {
                 let event = cef::MouseEvent {
-                    x: position.x as _,
-                    y: position.y as _,
+                    x: point.x as _,
+                    y: point.y as _,
                     modifiers: self.last_button_modifiers,
                 };
                 host.send_mouse_move_event(Some(&event), false as _);
@@ -462,6 +742,100 @@ impl CefComponent {
                 host.send_mouse_move_event(Some(&event), true as _);
             }
         }
+        CefAction::None
+    }
+
+    /// Evaluate `script` in the main frame and resolve with its json-serialized result.
+    /// Returned promises are awaited, navigating away fails with [`EvalError::Cancelled`].
+    pub fn eval(&self, script: impl AsRef<str>) -> Task<Result<serde_json::Value, EvalError>> {
+        let rx = self
+            .browser()
+            .ok_or(EvalError::NoBrowser)
+            .and_then(|browser| {
+                let frame = browser.main_frame().ok_or(EvalError::NoBrowser)?;
+                self.client
+                    .as_ref()
+                    .ok_or(EvalError::NoBrowser)?
+                    .eval
+                    .send(&frame, script.as_ref())
+            });
+        Task::future(async move {
+            match rx {
+                Ok(rx) => rx.await.unwrap_or(Err(EvalError::Cancelled)),
+                Err(err) => Err(err),
+            }
+        })
+    }
+
+    /// Like [`CefComponent::eval`] but deserializes the result into `T`.
+    pub fn eval_as<T>(&self, script: impl AsRef<str>) -> Task<Result<T, EvalError>>
+    where
+        T: serde::de::DeserializeOwned + Send + 'static,
+    {
+        self.eval(script).map(|result| {
+            result.and_then(|value| {
+                serde_json::from_value(value)
+                    .map_err(|err| EvalError::InvalidResult(err.to_string()))
+            })
+        })
+    }
+
+    /// Print the page into the pdf file at `path` and resolve with that path.
+    ///
+    /// Rendering happens offscreen, so this also works without a display.
+    pub fn print_to_pdf(
+        &self,
+        path: impl Into<camino::Utf8PathBuf>,
+        options: &PdfOptions,
+    ) -> Task<Result<camino::Utf8PathBuf, PdfError>> {
+        let path = path.into();
+        let rx = self
+            .browser()
+            .and_then(|browser| browser.host())
+            .map(|host| crate::pdf::print_to_pdf(&host, path.clone(), options));
+        Task::future(async move {
+            let Some(rx) = rx else {
+                return Err(PdfError::NoBrowser);
+            };
+            match rx.await {
+                Ok(true) => Ok(path),
+                Ok(false) => Err(PdfError::Failed),
+                Err(_) => Err(PdfError::Cancelled),
+            }
+        })
+    }
+
+    /// Capture `area` of the page as an rgba image
+    pub fn screenshot(&self, area: ScreenshotArea) -> Task<Result<Screenshot, ScreenshotError>> {
+        let Some(browser) = self.browser() else {
+            return Task::done(Err(ScreenshotError::NoBrowser));
+        };
+        let Some(state) = &self.client else {
+            return Task::done(Err(ScreenshotError::NoBrowser));
+        };
+        match area {
+            ScreenshotArea::Viewport => crate::screenshot::capture_frame(state, None),
+            ScreenshotArea::Region(region) => crate::screenshot::capture_frame(state, Some(region)),
+            ScreenshotArea::FullPage => {
+                let Some(host) = browser.host() else {
+                    return Task::done(Err(ScreenshotError::NoBrowser));
+                };
+                let rx = crate::screenshot::capture_full_page(
+                    &state.devtools,
+                    &host,
+                    state.render.device_scale_factor(),
+                );
+                Task::future(async move { rx.await.unwrap_or(Err(ScreenshotError::Cancelled)) })
+            }
+        }
+    }
+
+    /// Cookies of the request context this webview was created with
+    pub fn cookie_manager(&self) -> Option<CookieManager> {
+        self.host
+            .as_ref()
+            .and_then(|host| host.request_context())
+            .and_then(|context| CookieManager::new(&context))
     }
 
     pub fn get_window_info(
@@ -485,8 +859,17 @@ impl CefComponent {
         url: url::Url,
         bound: cef::Rect,
         device_scale_factor: f32,
+        options: WebviewOptions,
     ) -> Task<CefMessage> {
-        let (client, handlers) = IcyClient::new(launch_id, device_scale_factor, bound);
+        let mut extra_info = options.bindings.extra_info();
+        let profile = options.profile.clone().unwrap_or_else(Profile::incognito);
+        let mut context = profile.request_context();
+        // without a context cef silently falls back to the global one
+        if context.is_none() && !profile.is_global() {
+            tracing::error!(?profile, "cannot create the request context of the profile");
+            return Task::none();
+        }
+        let (client, handlers) = IcyClient::new(launch_id, device_scale_factor, bound, options);
         let IcyClient { state, subscribers } = client;
 
         let mut windowinfo = cef::WindowInfo {
@@ -498,13 +881,6 @@ impl CefComponent {
 
         windowinfo.runtime_style = cef::sys::cef_runtime_style_t::CEF_RUNTIME_STYLE_ALLOY.into();
 
-        let mut context = cef::request_context_create_context(
-            Some(&RequestContextSettings::default()),
-            Some(&mut RequestContextHandlerBuilder::build(
-                IcyRequestContextHandler {},
-            )),
-        );
-
         let browser_settings = cef::BrowserSettings {
             windowless_frame_rate: 60,
             default_encoding: "utf-8".into(),
@@ -525,7 +901,7 @@ impl CefComponent {
                     .into(),
             ),
             Some(&browser_settings),
-            None::<&mut cef::DictionaryValue>,
+            extra_info.as_mut(),
             context.as_mut(),
         );
         if ret != 1 {
@@ -534,37 +910,72 @@ impl CefComponent {
         }
 
         new_browser(launch_id, state);
+        Self::subscribe(subscribers)
+    }
 
+    fn subscribe(subscribers: ClientEventSubscriber) -> Task<CefMessage> {
         let ClientEventSubscriber {
             lifespan_rx,
             load_rx,
+            display_rx,
+            context_menu_rx,
+            download_rx,
+            file_dialog_rx,
+            js_dialog_rx,
+            find_rx,
             process_message_rx,
             render_rx,
         } = subscribers;
         Task::batch([
             Task::stream(UnboundedReceiverStream::new(render_rx)).map(CefMessage::UpdateView),
-            Task::stream(tokio_stream::wrappers::ReceiverStream::new(lifespan_rx)).map(
-                move |event| match event {
-                    LifeSpanEvent::Closed { browser_id } => CefMessage::Closed(browser_id.into()),
-                    LifeSpanEvent::Created { browser_id } => CefMessage::Created(browser_id),
-                },
-            ),
+            Task::stream(UnboundedReceiverStream::new(lifespan_rx)).map(move |event| match event {
+                LifeSpanEvent::Closed { browser_id } => CefMessage::Closed(browser_id.into()),
+                LifeSpanEvent::Created { browser_id } => CefMessage::Created(browser_id),
+                LifeSpanEvent::PopupRequested { request } => CefMessage::PopupRequested(request),
+                LifeSpanEvent::PopupOpened { request, popup } => {
+                    CefMessage::PopupOpened(request, popup)
+                }
+            }),
+            Task::stream(UnboundedReceiverStream::new(load_rx)).then(|event| {
+                let loaded = match &event {
+                    LoadEvent::Changed {
+                        browser_id,
+                        is_loading: false,
+                        ..
+                    } => Some(*browser_id),
+                    _ => None,
+                };
+                let event = Task::done(CefMessage::Load(event));
+                match loaded {
+                    Some(browser_id) => event.chain(Task::done(CefMessage::Loaded(browser_id))),
+                    None => event,
+                }
+            }),
+            Task::stream(UnboundedReceiverStream::new(display_rx)).then(|event| {
+                let address = match &event {
+                    DisplayEvent::AddressChanged {
+                        is_main_frame: true,
+                        url,
+                        ..
+                    } => Some(url.clone()),
+                    _ => None,
+                };
+                let event = Task::done(CefMessage::Display(event));
+                match address {
+                    Some(url) => event.chain(Task::done(CefMessage::RestoreZoom(url))),
+                    None => event,
+                }
+            }),
+            Task::stream(UnboundedReceiverStream::new(context_menu_rx))
+                .map(CefMessage::ContextMenu),
+            Task::stream(UnboundedReceiverStream::new(download_rx)).map(CefMessage::Download),
+            Task::stream(UnboundedReceiverStream::new(file_dialog_rx)).map(CefMessage::FileDialog),
+            Task::stream(UnboundedReceiverStream::new(js_dialog_rx)).map(CefMessage::JsDialog),
+            Task::stream(UnboundedReceiverStream::new(find_rx)).map(CefMessage::FindResult),
             Task::stream(UnboundedReceiverStream::new(process_message_rx)).map(|msg| match msg {
-                crate::client::CefIpcMessage::FocusedNodeChanged {
-                    browser_id,
-                    x,
-                    y,
-                    width,
-                    height,
-                } => CefMessage::FocusedNodeChanged(
-                    browser_id.into(),
-                    iced::Rectangle {
-                        x: x as _,
-                        y: y as _,
-                        width: width as _,
-                        height: height as _,
-                    },
-                ),
+                crate::client::CefIpcMessage::FocusedNodeChanged { browser_id, bounds } => {
+                    CefMessage::FocusedNodeChanged(browser_id.into(), bounds)
+                }
                 crate::client::CefIpcMessage::CaretPositionChanged { browser_id, offset } => {
                     CefMessage::UpdateCaretOffset(browser_id.into(), offset)
                 }
@@ -574,17 +985,49 @@ impl CefComponent {
 
     pub fn update(&mut self, action: CefMessage) -> CefAction {
         match action {
+            // a pending javascript dialog is modal, the page gets no keys until it is answered
+            CefMessage::InputMethodEvent(_) | CefMessage::KeyEvent(_)
+                if self.js_dialog.is_some() =>
+            {
+                CefAction::None
+            }
             CefMessage::InputMethodEvent(event) => {
                 self.send_ime_event(event, self.caret_offset);
                 CefAction::None
             }
-            CefMessage::KeyEvent(event) => {
-                self.send_key_event(event);
+            CefMessage::Focus(focused) => {
+                self.focused = focused;
+                if let Some(host) = &self.host {
+                    host.set_focus(focused as _);
+                }
                 CefAction::None
             }
-            CefMessage::MouseEvent(point, event) => {
-                self.send_mouse_event(point, event);
-                CefAction::None
+            CefMessage::KeyEvent(event) => self.send_key_event(event),
+            CefMessage::MouseEvent(point, event) => self.send_mouse_event(point, event),
+            CefMessage::ZoomIn => self.zoom_shortcut(zoom::ZoomShortcut::In),
+            CefMessage::ZoomOut => self.zoom_shortcut(zoom::ZoomShortcut::Out),
+            CefMessage::ZoomReset => self.zoom_shortcut(zoom::ZoomShortcut::Reset),
+            CefMessage::SetZoom(factor) => self.set_zoom(factor, true),
+            CefMessage::RestoreZoom(url) => {
+                let host = url::Url::parse(&url)
+                    .ok()
+                    .and_then(|url| url.host_str().map(str::to_string));
+                if host == self.zoom_host {
+                    return CefAction::None;
+                }
+                self.zoom_host = host;
+                let remembered = self.options.profile.as_ref().and_then(|profile| {
+                    profile.remembers_zoom().then(|| {
+                        self.zoom_host
+                            .as_deref()
+                            .and_then(|host| profile.zoom_for_host(host))
+                            .unwrap_or(1.0)
+                    })
+                });
+                match remembered {
+                    Some(factor) => self.set_zoom(factor, false),
+                    None => CefAction::None,
+                }
             }
             CefMessage::UpdateView(view) => {
                 self.view.replace(view);
@@ -602,6 +1045,7 @@ impl CefComponent {
                         height: size.height as _,
                     },
                     device_scale_factor,
+                    self.options.clone(),
                 ))
             }
             CefMessage::Created(browser_id) => {
@@ -610,8 +1054,10 @@ impl CefComponent {
                     .and_then(|b| b.host())
                 {
                     host.send_external_begin_frame();
+                    host.set_focus(self.focused as _);
                     self.host.replace(host);
                 }
+                self.client = WEBVIEW_STATES.with_borrow(|states| states.get(&browser_id).cloned());
 
                 CefAction::Created(browser_id)
             }
@@ -623,805 +1069,233 @@ impl CefComponent {
                 self.focused_node.replace(node);
                 CefAction::None
             }
-            CefMessage::Closed(browser_id) => CefAction::Closed(browser_id),
-            CefMessage::Loaded(browwser_id) => CefAction::Loaded(browwser_id),
-        }
-    }
-
-    pub fn view(&self) -> Element<'_, CefMessage> {
-        if let Some(view) = self.view.as_ref() {
-            iced::widget::responsive(|size| {
-                Webview::new(
-                    view.browser_id(),
-                    iced::widget::shader(view.clone())
-                        .width(size.width)
-                        .height(size.height),
-                )
-                .focused_node(self.focused_node)
-                .caret_offset(self.caret_offset)
-                .on_key_event(CefMessage::KeyEvent)
-                .on_input_method_event(CefMessage::InputMethodEvent)
-                .on_mouse_event(CefMessage::MouseEvent)
-                .into()
-            })
-            .into()
-        } else {
-            if let Some(host) = self.host.as_ref() {
-                host.send_external_begin_frame();
-            }
-            iced::widget::space().into()
-        }
-    }
-
-    pub fn subscription(&self) -> Subscription<CefMessage> {
-        Subscription::none()
-    }
-}
-
-fn to_native_key(keycode: Code) -> u32 {
-    match keycode {
-        Code::KeyA => {
-            if cfg!(target_os = "macos") {
-                0x00
-            } else {
-                0x41
-            }
-        }
-        Code::KeyB => {
-            if cfg!(target_os = "macos") {
-                0x0B
-            } else {
-                0x42
-            }
-        }
-        Code::KeyC => {
-            if cfg!(target_os = "macos") {
-                0x08
-            } else {
-                0x43
-            }
-        }
-        Code::KeyD => {
-            if cfg!(target_os = "macos") {
-                0x02
-            } else {
-                0x44
-            }
-        }
-        Code::KeyE => {
-            if cfg!(target_os = "macos") {
-                0x0E
-            } else {
-                0x45
-            }
-        }
-        Code::KeyF => {
-            if cfg!(target_os = "macos") {
-                0x03
-            } else {
-                0x46
-            }
-        }
-        Code::KeyG => {
-            if cfg!(target_os = "macos") {
-                0x05
-            } else {
-                0x47
-            }
-        }
-        Code::KeyH => {
-            if cfg!(target_os = "macos") {
-                0x04
-            } else {
-                0x48
-            }
-        }
-        Code::KeyI => {
-            if cfg!(target_os = "macos") {
-                0x22
-            } else {
-                0x49
-            }
-        }
-        Code::KeyJ => {
-            if cfg!(target_os = "macos") {
-                0x26
-            } else {
-                0x4A
-            }
-        }
-        Code::KeyK => {
-            if cfg!(target_os = "macos") {
-                0x28
-            } else {
-                0x4B
+            CefMessage::Closed(browser_id) => {
+                self.client.take();
+                CefAction::Closed(browser_id)
             }
-        }
-        Code::KeyL => {
-            if cfg!(target_os = "macos") {
-                0x25
-            } else {
-                0x4C
-            }
-        }
-        Code::KeyM => {
-            if cfg!(target_os = "macos") {
-                0x2E
-            } else {
-                0x4D
-            }
-        }
-        Code::KeyN => {
-            if cfg!(target_os = "macos") {
-                0x2D
-            } else {
-                0x4E
-            }
-        }
-        Code::KeyO => {
-            if cfg!(target_os = "macos") {
-                0x1F
-            } else {
-                0x4F
-            }
-        }
-        Code::KeyP => {
-            if cfg!(target_os = "macos") {
-                0x23
-            } else {
-                0x50
-            }
-        }
-        Code::KeyQ => {
-            if cfg!(target_os = "macos") {
-                0x0C
-            } else {
-                0x51
-            }
-        }
-        Code::KeyR => {
-            if cfg!(target_os = "macos") {
-                0x0F
-            } else {
-                0x52
-            }
-        }
-        Code::KeyS => {
-            if cfg!(target_os = "macos") {
-                0x01
-            } else {
-                0x53
-            }
-        }
-        Code::KeyT => {
-            if cfg!(target_os = "macos") {
-                0x11
-            } else {
-                0x54
-            }
-        }
-        Code::KeyU => {
-            if cfg!(target_os = "macos") {
-                0x20
-            } else {
-                0x55
-            }
-        }
-        Code::KeyV => {
-            if cfg!(target_os = "macos") {
-                0x09
-            } else {
-                0x56
-            }
-        }
-        Code::KeyW => {
-            if cfg!(target_os = "macos") {
-                0x0D
-            } else {
-                0x57
-            }
-        }
-        Code::KeyX => {
-            if cfg!(target_os = "macos") {
-                0x07
-            } else {
-                0x58
-            }
-        }
-        Code::KeyY => {
-            if cfg!(target_os = "macos") {
-                0x10
-            } else {
-                0x59
-            }
-        }
-        Code::KeyZ => {
-            if cfg!(target_os = "macos") {
-                0x06
-            } else {
-                0x5A
-            }
-        }
-
-        Code::Digit0 => {
-            if cfg!(target_os = "macos") {
-                0x1D
-            } else {
-                0x30
-            }
-        }
-        Code::Digit1 => {
-            if cfg!(target_os = "macos") {
-                0x12
-            } else {
-                0x31
-            }
-        }
-        Code::Digit2 => {
-            if cfg!(target_os = "macos") {
-                0x13
-            } else {
-                0x32
-            }
-        }
-        Code::Digit3 => {
-            if cfg!(target_os = "macos") {
-                0x14
-            } else {
-                0x33
-            }
-        }
-        Code::Digit4 => {
-            if cfg!(target_os = "macos") {
-                0x15
-            } else {
-                0x34
-            }
-        }
-        Code::Digit5 => {
-            if cfg!(target_os = "macos") {
-                0x17
-            } else {
-                0x35
-            }
-        }
-        Code::Digit6 => {
-            if cfg!(target_os = "macos") {
-                0x16
-            } else {
-                0x36
-            }
-        }
-        Code::Digit7 => {
-            if cfg!(target_os = "macos") {
-                0x1A
-            } else {
-                0x37
-            }
-        }
-        Code::Digit8 => {
-            if cfg!(target_os = "macos") {
-                0x1C
-            } else {
-                0x38
-            }
-        }
-        Code::Digit9 => {
-            if cfg!(target_os = "macos") {
-                0x19
-            } else {
-                0x39
-            }
-        }
-
-        // Function keys
-        Code::F1 => {
-            if cfg!(target_os = "macos") {
-                0x7A
-            } else {
-                0x70
-            }
-        }
-        Code::F2 => {
-            if cfg!(target_os = "macos") {
-                0x78
-            } else {
-                0x71
-            }
-        }
-        Code::F3 => {
-            if cfg!(target_os = "macos") {
-                0x63
-            } else {
-                0x72
-            }
-        }
-        Code::F4 => {
-            if cfg!(target_os = "macos") {
-                0x76
-            } else {
-                0x73
-            }
-        }
-        Code::F5 => {
-            if cfg!(target_os = "macos") {
-                0x60
-            } else {
-                0x74
-            }
-        }
-        Code::F6 => {
-            if cfg!(target_os = "macos") {
-                0x61
-            } else {
-                0x75
-            }
-        }
-        Code::F7 => {
-            if cfg!(target_os = "macos") {
-                0x62
-            } else {
-                0x76
-            }
-        }
-        Code::F8 => {
-            if cfg!(target_os = "macos") {
-                0x64
-            } else {
-                0x77
-            }
-        }
-        Code::F9 => {
-            if cfg!(target_os = "macos") {
-                0x65
-            } else {
-                0x78
-            }
-        }
-        Code::F10 => {
-            if cfg!(target_os = "macos") {
-                0x6D
-            } else {
-                0x79
-            }
-        }
-        Code::F11 => {
-            if cfg!(target_os = "macos") {
-                0x67
-            } else {
-                0x7A
-            }
-        }
-        Code::F12 => {
-            if cfg!(target_os = "macos") {
-                0x6F
-            } else {
-                0x7B
-            }
-        }
-
-        Code::Enter => {
-            if cfg!(target_os = "macos") {
-                0x24
-            } else {
-                0x0D
-            }
-        }
-        Code::Space => {
-            if cfg!(target_os = "macos") {
-                0x31
-            } else {
-                0x20
-            }
-        }
-        Code::Backspace => {
-            if cfg!(target_os = "macos") {
-                0x33
-            } else {
-                0x08
-            }
-        }
-        Code::Delete => {
-            if cfg!(target_os = "macos") {
-                0x75
-            } else {
-                0x2E
-            }
-        }
-        Code::Tab => {
-            if cfg!(target_os = "macos") {
-                0x30
-            } else {
-                0x09
-            }
-        }
-        Code::Escape => {
-            if cfg!(target_os = "macos") {
-                0x35
-            } else {
-                0x1B
-            }
-        }
-        Code::Insert => {
-            if cfg!(target_os = "macos") {
-                0x72
-            } else {
-                0x2D
-            }
-        }
-        Code::Home => {
-            if cfg!(target_os = "macos") {
-                0x73
-            } else {
-                0x24
-            }
-        }
-        Code::End => {
-            if cfg!(target_os = "macos") {
-                0x77
-            } else {
-                0x23
-            }
-        }
-        Code::PageUp => {
-            if cfg!(target_os = "macos") {
-                0x74
-            } else {
-                0x21
-            }
-        }
-        Code::PageDown => {
-            if cfg!(target_os = "macos") {
-                0x79
-            } else {
-                0x22
-            }
-        }
-
-        // Arrow keys
-        Code::ArrowLeft => {
-            if cfg!(target_os = "macos") {
-                0x7B
-            } else {
-                0x25
-            }
-        }
-        Code::ArrowUp => {
-            if cfg!(target_os = "macos") {
-                0x7E
-            } else {
-                0x26
-            }
-        }
-        Code::ArrowRight => {
-            if cfg!(target_os = "macos") {
-                0x7C
-            } else {
-                0x27
-            }
-        }
-        Code::ArrowDown => {
-            if cfg!(target_os = "macos") {
-                0x7D
-            } else {
-                0x28
-            }
-        }
-
-        // Modifier keys
-        Code::ShiftLeft => {
-            if cfg!(target_os = "macos") {
-                0x38
-            } else {
-                0xA0
-            }
-        }
-        Code::ShiftRight => {
-            if cfg!(target_os = "macos") {
-                0x3C
-            } else {
-                0xA1
-            }
-        }
-        Code::ControlLeft => {
-            if cfg!(target_os = "macos") {
-                0x3B
-            } else {
-                0xA2
+            CefMessage::PopupRequested(request) => CefAction::PopupRequested(request),
+            CefMessage::PopupOpened(request, popup) => CefAction::PopupOpened(request, popup),
+            CefMessage::ContextMenu(event) => {
+                match &event {
+                    ContextMenuEvent::Show(menu) => {
+                        self.context_menu.replace(menu.clone());
+                    }
+                    ContextMenuEvent::Dismissed { .. } => {
+                        self.context_menu.take();
+                    }
+                    ContextMenuEvent::Command { .. } => {}
+                }
+                CefAction::ContextMenu(event)
             }
-        }
-        Code::ControlRight => {
-            if cfg!(target_os = "macos") {
-                0x3E
-            } else {
-                0xA3
+            CefMessage::Download(event) => CefAction::Download(event),
+            CefMessage::DownloadCommand(id, command) => {
+                if let Some(client) = &self.client {
+                    client.download.run(id, command);
+                }
+                CefAction::None
             }
-        }
-        Code::AltLeft => {
-            if cfg!(target_os = "macos") {
-                0x3A
-            } else {
-                0xA4
+            CefMessage::FileDialog(request) => CefAction::FileDialog(request),
+            CefMessage::FileDialogResponse(id, paths) => {
+                if let Some(client) = &self.client {
+                    client.dialog.respond(id, paths);
+                }
+                CefAction::None
             }
-        }
-        Code::AltRight => {
-            if cfg!(target_os = "macos") {
-                0x3D
-            } else {
-                0xA5
+            CefMessage::JsDialog(event) => {
+                match &event {
+                    JsDialogEvent::Open(dialog) => {
+                        self.js_dialog_input = match &dialog.kind {
+                            crate::JsDialogKind::Prompt { default_text } => default_text.clone(),
+                            _ => String::new(),
+                        };
+                        self.js_dialog.replace(dialog.clone());
+                        if self.js_dialog_overlay
+                            && let crate::JsDialogKind::Prompt { .. } = dialog.kind
+                        {
+                            return CefAction::Run(iced::widget::operation::focus(
+                                crate::overlay::js_dialog_prompt_id(),
+                            ));
+                        }
+                    }
+                    JsDialogEvent::Reset { .. } => {
+                        self.js_dialog.take();
+                    }
+                }
+                CefAction::JsDialog(event)
             }
-        }
-        Code::SuperLeft => {
-            if cfg!(target_os = "macos") {
-                0x37
-            } else {
-                0x5B
+            CefMessage::JsDialogInput(input) => {
+                self.js_dialog_input = input;
+                CefAction::None
             }
-        }
-        Code::SuperRight => {
-            if cfg!(target_os = "macos") {
-                0x36
-            } else {
-                0x5C
+            CefMessage::JsDialogResponse(id, accepted, input) => {
+                if self
+                    .js_dialog
+                    .as_ref()
+                    .is_some_and(|dialog| dialog.id == id)
+                {
+                    self.js_dialog.take();
+                }
+                if let Some(client) = &self.client {
+                    client.js_dialog.respond(id, accepted, input);
+                }
+                CefAction::None
             }
-        }
-
-        // Lock keys
-        Code::CapsLock => {
-            if cfg!(target_os = "macos") {
-                0x39
-            } else {
-                0x14
+            CefMessage::FindResult(result) => {
+                if self.find_query.is_none() {
+                    return CefAction::None;
+                }
+                self.find_result.replace(result.clone());
+                CefAction::Find(result)
             }
-        }
-        Code::NumLock => {
-            if cfg!(target_os = "macos") {
-                0x47
-            } else {
-                0x90
+            CefMessage::Find(text, match_case) => {
+                if text.is_empty() {
+                    return self.update(CefMessage::StopFinding(true));
+                }
+                if let Some(host) = self.browser().and_then(|browser| browser.host()) {
+                    let find_next = self.find_query.as_ref() == Some(&(text.clone(), match_case));
+                    host.find(
+                        Some(&text.as_str().into()),
+                        true as _,
+                        match_case as _,
+                        find_next as _,
+                    );
+                }
+                self.find_query.replace((text, match_case));
+                CefAction::None
             }
-        }
-        Code::ScrollLock => 0x91,
-
-        Code::Semicolon => {
-            if cfg!(target_os = "macos") {
-                0x29
-            } else {
-                0xBA
+            CefMessage::FindNext => {
+                self.find_again(true);
+                CefAction::None
             }
-        }
-        Code::Equal => {
-            if cfg!(target_os = "macos") {
-                0x18
-            } else {
-                0xBB
+            CefMessage::FindPrevious => {
+                self.find_again(false);
+                CefAction::None
             }
-        }
-        Code::Comma => {
-            if cfg!(target_os = "macos") {
-                0x2B
-            } else {
-                0xBC
+            CefMessage::StopFinding(clear_selection) => {
+                self.find_query.take();
+                self.find_result.take();
+                if let Some(host) = self.browser().and_then(|browser| browser.host()) {
+                    host.stop_finding(clear_selection as _);
+                }
+                CefAction::None
             }
-        }
-        Code::Minus => {
-            if cfg!(target_os = "macos") {
-                0x1B
-            } else {
-                0xBD
+            CefMessage::ContextMenuCommand(command) => {
+                if let (Some(_), Some(client)) = (self.context_menu.take(), &self.client) {
+                    client.context_menu.select(command);
+                }
+                CefAction::None
             }
-        }
-        Code::Period => {
-            if cfg!(target_os = "macos") {
-                0x2F
-            } else {
-                0xBE
+            CefMessage::Loaded(browwser_id) => CefAction::Loaded(browwser_id),
+            CefMessage::Load(event) => {
+                if let LoadEvent::Changed {
+                    is_loading,
+                    can_go_back,
+                    can_go_forward,
+                    ..
+                } = event
+                {
+                    self.is_loading = is_loading;
+                    self.can_go_back = can_go_back;
+                    self.can_go_forward = can_go_forward;
+                }
+                CefAction::Load(event)
             }
-        }
-        Code::Slash => {
-            if cfg!(target_os = "macos") {
-                0x2C
-            } else {
-                0xBF
+            CefMessage::Display(event) => CefAction::Display(event),
+            CefMessage::LoadUrl(url) => {
+                if let Some(frame) = self.browser().and_then(|browser| browser.main_frame()) {
+                    frame.load_url(Some(&url.as_str().into()));
+                }
+                CefAction::None
             }
-        }
-        Code::Backquote => {
-            if cfg!(target_os = "macos") {
-                0x32
-            } else {
-                0xC0
+            CefMessage::GoBack => {
+                if let Some(browser) = self.browser() {
+                    browser.go_back();
+                }
+                CefAction::None
             }
-        }
-        Code::BracketLeft => {
-            if cfg!(target_os = "macos") {
-                0x21
-            } else {
-                0xDB
+            CefMessage::GoForward => {
+                if let Some(browser) = self.browser() {
+                    browser.go_forward();
+                }
+                CefAction::None
             }
-        }
-        Code::Backslash => {
-            if cfg!(target_os = "macos") {
-                0x2A
-            } else {
-                0xDC
+            CefMessage::Reload => {
+                if let Some(browser) = self.browser() {
+                    browser.reload();
+                }
+                CefAction::None
             }
-        }
-        Code::BracketRight => {
-            if cfg!(target_os = "macos") {
-                0x1E
-            } else {
-                0xDD
+            CefMessage::ReloadIgnoreCache => {
+                if let Some(browser) = self.browser() {
+                    browser.reload_ignore_cache();
+                }
+                CefAction::None
             }
-        }
-        Code::Quote => {
-            if cfg!(target_os = "macos") {
-                0x27
-            } else {
-                0xDE
+            CefMessage::StopLoad => {
+                if let Some(browser) = self.browser() {
+                    browser.stop_load();
+                }
+                CefAction::None
             }
         }
+    }
 
-        Code::Numpad0 => {
-            if cfg!(target_os = "macos") {
-                0x52
-            } else {
-                0x60
-            }
-        }
-        Code::Numpad1 => {
-            if cfg!(target_os = "macos") {
-                0x53
-            } else {
-                0x61
-            }
-        }
-        Code::Numpad2 => {
-            if cfg!(target_os = "macos") {
-                0x54
-            } else {
-                0x62
-            }
-        }
-        Code::Numpad3 => {
-            if cfg!(target_os = "macos") {
-                0x55
-            } else {
-                0x63
-            }
-        }
-        Code::Numpad4 => {
-            if cfg!(target_os = "macos") {
-                0x56
-            } else {
-                0x64
-            }
-        }
-        Code::Numpad5 => {
-            if cfg!(target_os = "macos") {
-                0x57
-            } else {
-                0x65
-            }
-        }
-        Code::Numpad6 => {
-            if cfg!(target_os = "macos") {
-                0x58
-            } else {
-                0x66
-            }
-        }
-        Code::Numpad7 => {
-            if cfg!(target_os = "macos") {
-                0x59
-            } else {
-                0x67
-            }
-        }
-        Code::Numpad8 => {
-            if cfg!(target_os = "macos") {
-                0x5B
-            } else {
-                0x68
-            }
-        }
-        Code::Numpad9 => {
-            if cfg!(target_os = "macos") {
-                0x5C
-            } else {
-                0x69
-            }
-        }
-        Code::NumpadMultiply => {
-            if cfg!(target_os = "macos") {
-                0x43
-            } else {
-                0x6A
-            }
-        }
-        Code::NumpadAdd => {
-            if cfg!(target_os = "macos") {
-                0x45
-            } else {
-                0x6B
-            }
-        }
-        Code::NumpadSubtract => {
-            if cfg!(target_os = "macos") {
-                0x4E
-            } else {
-                0x6D
+    pub fn view(&self) -> Element<'_, CefMessage> {
+        if let Some(view) = self.view.as_ref() {
+            let webview = iced::widget::responsive(|size| {
+                let webview = Webview::new(
+                    iced::widget::shader(view.clone())
+                        .width(size.width)
+                        .height(size.height),
+                );
+                match (&self.host, &self.client) {
+                    (Some(host), Some(client)) => webview.browser(host.clone(), client.clone()),
+                    _ => webview,
+                }
+                .focused_node(self.focused_node)
+                .caret_offset(self.caret_offset)
+                // keys and the input method go to the dialog while one is pending
+                .focused(self.focused && self.js_dialog.is_none())
+                .on_focus(CefMessage::Focus)
+                .on_key_event(CefMessage::KeyEvent)
+                .on_input_method_event(CefMessage::InputMethodEvent)
+                .on_mouse_event(CefMessage::MouseEvent)
+                .into()
+            });
+            let mut layers: Vec<Element<'_, CefMessage>> = vec![webview.into()];
+            if let Some(menu) = self.context_menu.as_ref()
+                && self.context_menu_overlay
+            {
+                layers.push(crate::overlay::context_menu(
+                    menu,
+                    CefMessage::ContextMenuCommand,
+                ));
             }
-        }
-        Code::NumpadDecimal => {
-            if cfg!(target_os = "macos") {
-                0x41
-            } else {
-                0x6E
+            if let Some(dialog) = self.js_dialog.as_ref()
+                && self.js_dialog_overlay
+            {
+                let id = dialog.id;
+                let overlay = crate::overlay::js_dialog(
+                    dialog,
+                    &self.js_dialog_input,
+                    CefMessage::JsDialogInput,
+                    move |accepted, input| CefMessage::JsDialogResponse(id, accepted, input),
+                );
+                layers.push(match self.js_dialog_style.clone() {
+                    Some(style) => overlay.style(move |theme| style(theme)).into(),
+                    None => overlay.into(),
+                });
             }
-        }
-        Code::NumpadDivide => {
-            if cfg!(target_os = "macos") {
-                0x4B
-            } else {
-                0x6F
+            iced::widget::Stack::with_children(layers).into()
+        } else {
+            if let Some(host) = self.host.as_ref() {
+                host.send_external_begin_frame();
             }
+            iced::widget::space().into()
         }
-
-        _ => 0,
     }
-}
 
-fn to_virtual_key(keycode: Named) -> i32 {
-    match keycode {
-        Named::F1 => 0x70,
-        Named::F2 => 0x71,
-        Named::F3 => 0x72,
-        Named::F4 => 0x73,
-        Named::F5 => 0x74,
-        Named::F6 => 0x75,
-        Named::F7 => 0x76,
-        Named::F8 => 0x77,
-        Named::F9 => 0x78,
-        Named::F10 => 0x79,
-        Named::F11 => 0x7A,
-        Named::F12 => 0x7B,
-
-        Named::Enter => 0x0D,
-        Named::Space => 0x20,
-        Named::Backspace => 0x08,
-        Named::Delete => 0x2E,
-        Named::Tab => 0x09,
-        Named::Escape => 0x1B,
-        Named::Insert => 0x2D,
-        Named::Home => 0x24,
-        Named::End => 0x23,
-        Named::PageUp => 0x21,
-        Named::PageDown => 0x22,
-
-        Named::ArrowLeft => 0x25,
-        Named::ArrowUp => 0x26,
-        Named::ArrowRight => 0x27,
-        Named::ArrowDown => 0x28,
-
-        Named::Shift => 0x10,
-        Named::Control => 0x11,
-        Named::Alt => 0x12,
-        Named::Super => 0x5B,
-
-        Named::CapsLock => 0x14,
-        Named::NumLock => 0x90,
-        Named::ScrollLock => 0x91,
-        _ => 0,
-    }
-}
-
-fn named_key_to_text(named_key: Named) -> Option<char> {
-    match named_key {
-        Named::Enter => Some('\r'),
-        Named::Backspace => Some('\x08'),
-        Named::Tab => Some('\t'),
-        Named::Space => Some(' '),
-        Named::Escape => Some('\x1b'),
-        _ => None,
+    pub fn subscription(&self) -> Subscription<CefMessage> {
+        Subscription::none()
     }
 }
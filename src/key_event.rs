@@ -0,0 +1,897 @@
+//! Translating iced keyboard events into the key events cef expects
+//!
+//! A key press becomes a `KEYEVENT_RAWKEYDOWN` followed by one `KEYEVENT_CHAR` per utf-16
+//! unit of the typed text, a release becomes a `KEYEVENT_KEYUP`. Nothing here talks to cef,
+//! so the tables are tested without a browser.
+
+use cef::sys::{cef_event_flags_t, cef_key_event_type_t};
+use iced::keyboard::{
+    self, Key, Location, Modifiers,
+    key::{Code, Named, Physical},
+};
+
+const SHIFT: u32 = cef_event_flags_t::EVENTFLAG_SHIFT_DOWN.0;
+const CONTROL: u32 = cef_event_flags_t::EVENTFLAG_CONTROL_DOWN.0;
+const ALT: u32 = cef_event_flags_t::EVENTFLAG_ALT_DOWN.0;
+const COMMAND: u32 = cef_event_flags_t::EVENTFLAG_COMMAND_DOWN.0;
+const KEYPAD: u32 = cef_event_flags_t::EVENTFLAG_IS_KEY_PAD.0;
+const LEFT: u32 = cef_event_flags_t::EVENTFLAG_IS_LEFT.0;
+const RIGHT: u32 = cef_event_flags_t::EVENTFLAG_IS_RIGHT.0;
+const ALTGR: u32 = cef_event_flags_t::EVENTFLAG_ALTGR_DOWN.0;
+const REPEAT: u32 = cef_event_flags_t::EVENTFLAG_IS_REPEAT.0;
+
+/// Decides the meaning of `native_key_code` and a few platform conventions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Platform {
+    /// `lParam` of `WM_KEYDOWN`, `WM_CHAR` carries the character in `windows_key_code`
+    Windows,
+    /// `kVK_*` virtual key code of carbon
+    MacOs,
+    /// X11 keycode, the evdev scancode plus 8
+    Linux,
+}
+
+impl Platform {
+    pub(crate) const fn current() -> Self {
+        if cfg!(target_os = "windows") {
+            Self::Windows
+        } else if cfg!(target_os = "macos") {
+            Self::MacOs
+        } else {
+            Self::Linux
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum KeyStrokeKind {
+    RawKeyDown,
+    KeyUp,
+    Char,
+}
+
+/// One key event for cef, see [`KeyStroke::to_cef`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct KeyStroke {
+    pub kind: KeyStrokeKind,
+    /// `EVENTFLAG_*` bits
+    pub modifiers: u32,
+    pub windows_key_code: i32,
+    pub native_key_code: i32,
+    pub character: u16,
+    pub unmodified_character: u16,
+    pub is_system_key: bool,
+}
+
+impl KeyStroke {
+    pub(crate) fn to_cef(self) -> cef::KeyEvent {
+        let type_ = match self.kind {
+            KeyStrokeKind::RawKeyDown => cef_key_event_type_t::KEYEVENT_RAWKEYDOWN,
+            KeyStrokeKind::KeyUp => cef_key_event_type_t::KEYEVENT_KEYUP,
+            KeyStrokeKind::Char => cef_key_event_type_t::KEYEVENT_CHAR,
+        };
+        let mut event = cef::KeyEvent::default();
+        event.type_ = type_.into();
+        event.modifiers = self.modifiers;
+        event.windows_key_code = self.windows_key_code as _;
+        event.native_key_code = self.native_key_code as _;
+        event.is_system_key = self.is_system_key as _;
+        event.character = self.character as _;
+        event.unmodified_character = self.unmodified_character as _;
+        event.focus_on_editable_field = false as _;
+        event
+    }
+}
+
+/// Key events to send for `event`, empty for modifier changes
+pub(crate) fn translate(event: &keyboard::Event, platform: Platform) -> Vec<KeyStroke> {
+    match event {
+        keyboard::Event::KeyPressed {
+            key,
+            physical_key,
+            location,
+            modifiers,
+            text,
+            repeat,
+            ..
+        } => press(
+            key,
+            *physical_key,
+            *location,
+            *modifiers,
+            text.as_deref(),
+            *repeat,
+            platform,
+        ),
+        keyboard::Event::KeyReleased {
+            key,
+            physical_key,
+            location,
+            modifiers,
+            ..
+        } => vec![stroke(
+            KeyStrokeKind::KeyUp,
+            key,
+            *physical_key,
+            *location,
+            *modifiers,
+            platform,
+        )],
+        keyboard::Event::ModifiersChanged(_) => Vec::new(),
+    }
+}
+
+fn press(
+    key: &Key,
+    physical_key: Physical,
+    location: Location,
+    modifiers: Modifiers,
+    text: Option<&str>,
+    repeat: bool,
+    platform: Platform,
+) -> Vec<KeyStroke> {
+    let mut raw = stroke(
+        KeyStrokeKind::RawKeyDown,
+        key,
+        physical_key,
+        location,
+        modifiers,
+        platform,
+    );
+    if repeat {
+        raw.modifiers |= REPEAT;
+    }
+
+    let typed: Vec<u16> = typed_text(key, text).encode_utf16().collect();
+    // windows reports AltGr as Ctrl+Alt, a character typed with both is not a shortcut
+    let altgr = modifiers.control() && modifiers.alt() && !typed.is_empty();
+    if altgr {
+        raw.modifiers |= ALTGR;
+    }
+    raw.character = typed.first().copied().unwrap_or(raw.unmodified_character);
+
+    let shortcut = !altgr && (modifiers.control() || modifiers.logo());
+    let mut strokes = vec![raw];
+    if shortcut {
+        return strokes;
+    }
+    strokes.extend(typed.into_iter().map(|unit| KeyStroke {
+        kind: KeyStrokeKind::Char,
+        windows_key_code: match platform {
+            Platform::Windows => unit as _,
+            Platform::MacOs | Platform::Linux => raw.windows_key_code,
+        },
+        character: unit,
+        ..raw
+    }));
+    strokes
+}
+
+/// The `RawKeyDown` or `KeyUp` of a key, without text
+fn stroke(
+    kind: KeyStrokeKind,
+    key: &Key,
+    physical_key: Physical,
+    location: Location,
+    modifiers: Modifiers,
+    platform: Platform,
+) -> KeyStroke {
+    let code = match physical_key {
+        Physical::Code(code) => Some(code),
+        Physical::Unidentified(_) => None,
+    };
+    let mut flags = modifier_flags(modifiers)
+        | match location {
+            Location::Standard => 0,
+            Location::Left => LEFT,
+            Location::Right => RIGHT,
+            Location::Numpad => KEYPAD,
+        };
+    if let Key::Named(Named::AltGraph) = key {
+        flags |= ALTGR;
+    }
+    KeyStroke {
+        kind,
+        modifiers: flags,
+        windows_key_code: virtual_key(key, code, location),
+        native_key_code: code
+            .and_then(scan_code)
+            .map(|scan| scan.native(platform, kind))
+            .unwrap_or_default(),
+        character: 0,
+        unmodified_character: unmodified_character(key),
+        is_system_key: platform == Platform::Windows && modifiers.alt() && !modifiers.control(),
+    }
+}
+
+fn modifier_flags(modifiers: Modifiers) -> u32 {
+    [
+        (modifiers.shift(), SHIFT),
+        (modifiers.control(), CONTROL),
+        (modifiers.alt(), ALT),
+        (modifiers.logo(), COMMAND),
+    ]
+    .into_iter()
+    .filter(|(down, _)| *down)
+    .fold(0, |flags, (_, flag)| flags | flag)
+}
+
+/// The text a press types, control characters other than enter and tab type nothing
+fn typed_text(key: &Key, text: Option<&str>) -> String {
+    let text = match (text, key) {
+        (Some(text), _) if !text.is_empty() => text,
+        (_, Key::Named(Named::Enter)) => "\r",
+        (_, Key::Named(Named::Tab)) => "\t",
+        (_, Key::Named(Named::Space)) => " ",
+        _ => "",
+    };
+    text.chars()
+        .filter(|c| !c.is_control() || matches!(c, '\r' | '\t'))
+        .collect()
+}
+
+fn unmodified_character(key: &Key) -> u16 {
+    let c = match key {
+        Key::Character(c) => c.chars().next(),
+        Key::Named(Named::Enter) => Some('\r'),
+        Key::Named(Named::Tab) => Some('\t'),
+        Key::Named(Named::Space) => Some(' '),
+        Key::Named(Named::Backspace) => Some('\x08'),
+        Key::Named(Named::Escape) => Some('\x1b'),
+        _ => None,
+    };
+    c.and_then(|c| c.encode_utf16(&mut [0; 2]).first().copied())
+        .unwrap_or_default()
+}
+
+/// `VK_*` code of the layout: letters and digits by their character so shortcuts follow the
+/// layout, keys a latin layout does not know by their position on a US keyboard
+fn virtual_key(key: &Key, code: Option<Code>, location: Location) -> i32 {
+    let by_position = || {
+        code.and_then(scan_code)
+            .map(|scan| scan.vk)
+            .unwrap_or_default()
+    };
+    match key {
+        Key::Character(_) if location == Location::Numpad => by_position(),
+        Key::Character(c) => c
+            .chars()
+            .next()
+            .and_then(character_vk)
+            .unwrap_or_else(by_position),
+        Key::Named(named) => match named_vk(*named, location) {
+            0 => by_position(),
+            vk => vk,
+        },
+        // dead keys and keys the platform cannot name
+        Key::Unidentified => by_position(),
+    }
+}
+
+fn character_vk(c: char) -> Option<i32> {
+    if c.is_ascii_alphanumeric() {
+        return Some(c.to_ascii_uppercase() as _);
+    }
+    let vk = match c {
+        ';' | ':' => 0xBA,
+        '=' | '+' => 0xBB,
+        ',' | '<' => 0xBC,
+        '-' | '_' => 0xBD,
+        '.' | '>' => 0xBE,
+        '/' | '?' => 0xBF,
+        '`' | '~' => 0xC0,
+        '[' | '{' => 0xDB,
+        '\\' | '|' => 0xDC,
+        ']' | '}' => 0xDD,
+        '\'' | '"' => 0xDE,
+        ' ' => 0x20,
+        _ => return None,
+    };
+    Some(vk)
+}
+
+fn named_vk(named: Named, location: Location) -> i32 {
+    match named {
+        Named::Backspace => 0x08,
+        Named::Tab => 0x09,
+        Named::Clear => 0x0C,
+        Named::Enter => 0x0D,
+        Named::Shift => 0x10,
+        Named::Control => 0x11,
+        Named::Alt | Named::AltGraph => 0x12,
+        Named::Pause => 0x13,
+        Named::CapsLock => 0x14,
+        Named::Escape => 0x1B,
+        Named::Space => 0x20,
+        Named::PageUp => 0x21,
+        Named::PageDown => 0x22,
+        Named::End => 0x23,
+        Named::Home => 0x24,
+        Named::ArrowLeft => 0x25,
+        Named::ArrowUp => 0x26,
+        Named::ArrowRight => 0x27,
+        Named::ArrowDown => 0x28,
+        Named::PrintScreen => 0x2C,
+        Named::Insert => 0x2D,
+        Named::Delete => 0x2E,
+        Named::Super | Named::Meta if location == Location::Right => 0x5C,
+        Named::Super | Named::Meta => 0x5B,
+        Named::ContextMenu => 0x5D,
+        Named::F1 => 0x70,
+        Named::F2 => 0x71,
+        Named::F3 => 0x72,
+        Named::F4 => 0x73,
+        Named::F5 => 0x74,
+        Named::F6 => 0x75,
+        Named::F7 => 0x76,
+        Named::F8 => 0x77,
+        Named::F9 => 0x78,
+        Named::F10 => 0x79,
+        Named::F11 => 0x7A,
+        Named::F12 => 0x7B,
+        Named::F13 => 0x7C,
+        Named::F14 => 0x7D,
+        Named::F15 => 0x7E,
+        Named::F16 => 0x7F,
+        Named::F17 => 0x80,
+        Named::F18 => 0x81,
+        Named::F19 => 0x82,
+        Named::F20 => 0x83,
+        Named::F21 => 0x84,
+        Named::F22 => 0x85,
+        Named::F23 => 0x86,
+        Named::F24 => 0x87,
+        Named::NumLock => 0x90,
+        Named::ScrollLock => 0x91,
+        Named::BrowserBack => 0xA6,
+        Named::BrowserForward => 0xA7,
+        Named::BrowserRefresh => 0xA8,
+        Named::AudioVolumeMute => 0xAD,
+        Named::AudioVolumeDown => 0xAE,
+        Named::AudioVolumeUp => 0xAF,
+        Named::MediaTrackNext => 0xB0,
+        Named::MediaTrackPrevious => 0xB1,
+        Named::MediaStop => 0xB2,
+        Named::MediaPlayPause => 0xB3,
+        _ => 0,
+    }
+}
+
+/// Where a physical key sits on a US keyboard
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ScanCode {
+    vk: i32,
+    evdev: u16,
+    /// set 1 scancode, `0xE0` in the high byte for extended keys
+    windows: u16,
+    mac: u16,
+}
+
+impl ScanCode {
+    fn native(self, platform: Platform, kind: KeyStrokeKind) -> i32 {
+        match platform {
+            Platform::Linux => self.evdev as i32 + 8,
+            Platform::MacOs => self.mac as _,
+            Platform::Windows => {
+                // repeat count 1, the scancode and the extended bit of `lParam`
+                let mut lparam = 1 | (self.windows as u32 & 0xFF) << 16;
+                if self.windows & 0xFF00 == 0xE000 {
+                    lparam |= 1 << 24;
+                }
+                if kind == KeyStrokeKind::KeyUp {
+                    // previous state down, transition up
+                    lparam |= 0b11 << 30;
+                }
+                lparam as i32
+            }
+        }
+    }
+}
+
+fn scan_code(code: Code) -> Option<ScanCode> {
+    let (vk, evdev, windows, mac) = match code {
+        Code::Escape => (0x1B, 1, 0x01, 0x35),
+        Code::Digit1 => (0x31, 2, 0x02, 0x12),
+        Code::Digit2 => (0x32, 3, 0x03, 0x13),
+        Code::Digit3 => (0x33, 4, 0x04, 0x14),
+        Code::Digit4 => (0x34, 5, 0x05, 0x15),
+        Code::Digit5 => (0x35, 6, 0x06, 0x17),
+        Code::Digit6 => (0x36, 7, 0x07, 0x16),
+        Code::Digit7 => (0x37, 8, 0x08, 0x1A),
+        Code::Digit8 => (0x38, 9, 0x09, 0x1C),
+        Code::Digit9 => (0x39, 10, 0x0A, 0x19),
+        Code::Digit0 => (0x30, 11, 0x0B, 0x1D),
+        Code::Minus => (0xBD, 12, 0x0C, 0x1B),
+        Code::Equal => (0xBB, 13, 0x0D, 0x18),
+        Code::Backspace => (0x08, 14, 0x0E, 0x33),
+        Code::Tab => (0x09, 15, 0x0F, 0x30),
+        Code::KeyQ => (0x51, 16, 0x10, 0x0C),
+        Code::KeyW => (0x57, 17, 0x11, 0x0D),
+        Code::KeyE => (0x45, 18, 0x12, 0x0E),
+        Code::KeyR => (0x52, 19, 0x13, 0x0F),
+        Code::KeyT => (0x54, 20, 0x14, 0x11),
+        Code::KeyY => (0x59, 21, 0x15, 0x10),
+        Code::KeyU => (0x55, 22, 0x16, 0x20),
+        Code::KeyI => (0x49, 23, 0x17, 0x22),
+        Code::KeyO => (0x4F, 24, 0x18, 0x1F),
+        Code::KeyP => (0x50, 25, 0x19, 0x23),
+        Code::BracketLeft => (0xDB, 26, 0x1A, 0x21),
+        Code::BracketRight => (0xDD, 27, 0x1B, 0x1E),
+        Code::Enter => (0x0D, 28, 0x1C, 0x24),
+        Code::ControlLeft => (0x11, 29, 0x1D, 0x3B),
+        Code::KeyA => (0x41, 30, 0x1E, 0x00),
+        Code::KeyS => (0x53, 31, 0x1F, 0x01),
+        Code::KeyD => (0x44, 32, 0x20, 0x02),
+        Code::KeyF => (0x46, 33, 0x21, 0x03),
+        Code::KeyG => (0x47, 34, 0x22, 0x05),
+        Code::KeyH => (0x48, 35, 0x23, 0x04),
+        Code::KeyJ => (0x4A, 36, 0x24, 0x26),
+        Code::KeyK => (0x4B, 37, 0x25, 0x28),
+        Code::KeyL => (0x4C, 38, 0x26, 0x25),
+        Code::Semicolon => (0xBA, 39, 0x27, 0x29),
+        Code::Quote => (0xDE, 40, 0x28, 0x27),
+        Code::Backquote => (0xC0, 41, 0x29, 0x32),
+        Code::ShiftLeft => (0x10, 42, 0x2A, 0x38),
+        Code::Backslash => (0xDC, 43, 0x2B, 0x2A),
+        Code::KeyZ => (0x5A, 44, 0x2C, 0x06),
+        Code::KeyX => (0x58, 45, 0x2D, 0x07),
+        Code::KeyC => (0x43, 46, 0x2E, 0x08),
+        Code::KeyV => (0x56, 47, 0x2F, 0x09),
+        Code::KeyB => (0x42, 48, 0x30, 0x0B),
+        Code::KeyN => (0x4E, 49, 0x31, 0x2D),
+        Code::KeyM => (0x4D, 50, 0x32, 0x2E),
+        Code::Comma => (0xBC, 51, 0x33, 0x2B),
+        Code::Period => (0xBE, 52, 0x34, 0x2F),
+        Code::Slash => (0xBF, 53, 0x35, 0x2C),
+        Code::ShiftRight => (0x10, 54, 0x36, 0x3C),
+        Code::NumpadMultiply => (0x6A, 55, 0x37, 0x43),
+        Code::AltLeft => (0x12, 56, 0x38, 0x3A),
+        Code::Space => (0x20, 57, 0x39, 0x31),
+        Code::CapsLock => (0x14, 58, 0x3A, 0x39),
+        Code::F1 => (0x70, 59, 0x3B, 0x7A),
+        Code::F2 => (0x71, 60, 0x3C, 0x78),
+        Code::F3 => (0x72, 61, 0x3D, 0x63),
+        Code::F4 => (0x73, 62, 0x3E, 0x76),
+        Code::F5 => (0x74, 63, 0x3F, 0x60),
+        Code::F6 => (0x75, 64, 0x40, 0x61),
+        Code::F7 => (0x76, 65, 0x41, 0x62),
+        Code::F8 => (0x77, 66, 0x42, 0x64),
+        Code::F9 => (0x78, 67, 0x43, 0x65),
+        Code::F10 => (0x79, 68, 0x44, 0x6D),
+        Code::NumLock => (0x90, 69, 0xE045, 0x47),
+        Code::ScrollLock => (0x91, 70, 0x46, 0x6B),
+        Code::Numpad7 => (0x67, 71, 0x47, 0x59),
+        Code::Numpad8 => (0x68, 72, 0x48, 0x5B),
+        Code::Numpad9 => (0x69, 73, 0x49, 0x5C),
+        Code::NumpadSubtract => (0x6D, 74, 0x4A, 0x4E),
+        Code::Numpad4 => (0x64, 75, 0x4B, 0x56),
+        Code::Numpad5 => (0x65, 76, 0x4C, 0x57),
+        Code::Numpad6 => (0x66, 77, 0x4D, 0x58),
+        Code::NumpadAdd => (0x6B, 78, 0x4E, 0x45),
+        Code::Numpad1 => (0x61, 79, 0x4F, 0x53),
+        Code::Numpad2 => (0x62, 80, 0x50, 0x54),
+        Code::Numpad3 => (0x63, 81, 0x51, 0x55),
+        Code::Numpad0 => (0x60, 82, 0x52, 0x52),
+        Code::NumpadDecimal => (0x6E, 83, 0x53, 0x41),
+        Code::IntlBackslash => (0xE2, 86, 0x56, 0x0A),
+        Code::F11 => (0x7A, 87, 0x57, 0x67),
+        Code::F12 => (0x7B, 88, 0x58, 0x6F),
+        Code::NumpadEnter => (0x0D, 96, 0xE01C, 0x4C),
+        Code::ControlRight => (0x11, 97, 0xE01D, 0x3E),
+        Code::NumpadDivide => (0x6F, 98, 0xE035, 0x4B),
+        Code::PrintScreen => (0x2C, 99, 0xE037, 0x69),
+        Code::AltRight => (0x12, 100, 0xE038, 0x3D),
+        Code::Home => (0x24, 102, 0xE047, 0x73),
+        Code::ArrowUp => (0x26, 103, 0xE048, 0x7E),
+        Code::PageUp => (0x21, 104, 0xE049, 0x74),
+        Code::ArrowLeft => (0x25, 105, 0xE04B, 0x7B),
+        Code::ArrowRight => (0x27, 106, 0xE04D, 0x7C),
+        Code::End => (0x23, 107, 0xE04F, 0x77),
+        Code::ArrowDown => (0x28, 108, 0xE050, 0x7D),
+        Code::PageDown => (0x22, 109, 0xE051, 0x79),
+        Code::Insert => (0x2D, 110, 0xE052, 0x72),
+        Code::Delete => (0x2E, 111, 0xE053, 0x75),
+        Code::Pause => (0x13, 119, 0x45, 0x71),
+        Code::SuperLeft => (0x5B, 125, 0xE05B, 0x37),
+        Code::SuperRight => (0x5C, 126, 0xE05C, 0x36),
+        Code::ContextMenu => (0x5D, 127, 0xE05D, 0x6E),
+        _ => return None,
+    };
+    Some(ScanCode {
+        vk,
+        evdev,
+        windows,
+        mac,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyStrokeKind::{Char, KeyUp, RawKeyDown};
+    use super::*;
+
+    fn pressed(
+        key: Key,
+        code: Code,
+        location: Location,
+        modifiers: Modifiers,
+        text: Option<&str>,
+    ) -> keyboard::Event {
+        keyboard::Event::KeyPressed {
+            modified_key: key.clone(),
+            key,
+            physical_key: Physical::Code(code),
+            location,
+            modifiers,
+            text: text.map(Into::into),
+            repeat: false,
+        }
+    }
+
+    fn released(key: Key, code: Code, location: Location, modifiers: Modifiers) -> keyboard::Event {
+        keyboard::Event::KeyReleased {
+            modified_key: key.clone(),
+            key,
+            physical_key: Physical::Code(code),
+            location,
+            modifiers,
+        }
+    }
+
+    fn character(c: &str) -> Key {
+        Key::Character(c.into())
+    }
+
+    /// `(kind, windows_key_code, modifiers, character)` of every stroke
+    fn summary(strokes: &[KeyStroke]) -> Vec<(KeyStrokeKind, i32, u32, u16)> {
+        strokes
+            .iter()
+            .map(|stroke| {
+                (
+                    stroke.kind,
+                    stroke.windows_key_code,
+                    stroke.modifiers,
+                    stroke.character,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn presses_and_releases() {
+        use Location::{Left, Numpad, Right, Standard};
+
+        let none = Modifiers::empty();
+        let cases = [
+            (
+                "letter",
+                pressed(character("a"), Code::KeyA, Standard, none, Some("a")),
+                vec![
+                    (RawKeyDown, 0x41, 0, 'a' as u16),
+                    (Char, 0x41, 0, 'a' as u16),
+                ],
+            ),
+            (
+                "shifted letter",
+                pressed(
+                    character("a"),
+                    Code::KeyA,
+                    Standard,
+                    Modifiers::SHIFT,
+                    Some("A"),
+                ),
+                vec![
+                    (RawKeyDown, 0x41, SHIFT, 'A' as u16),
+                    (Char, 0x41, SHIFT, 'A' as u16),
+                ],
+            ),
+            (
+                "ctrl+shift keeps both modifiers and types nothing",
+                pressed(
+                    character("z"),
+                    Code::KeyZ,
+                    Standard,
+                    Modifiers::CTRL | Modifiers::SHIFT,
+                    None,
+                ),
+                vec![(RawKeyDown, 0x5A, CONTROL | SHIFT, 'z' as u16)],
+            ),
+            (
+                "alt+shift keeps both modifiers",
+                pressed(
+                    character("1"),
+                    Code::Digit1,
+                    Standard,
+                    Modifiers::ALT | Modifiers::SHIFT,
+                    Some("!"),
+                ),
+                vec![
+                    (RawKeyDown, 0x31, ALT | SHIFT, '!' as u16),
+                    (Char, 0x31, ALT | SHIFT, '!' as u16),
+                ],
+            ),
+            (
+                "command shortcut",
+                pressed(
+                    character("c"),
+                    Code::KeyC,
+                    Standard,
+                    Modifiers::LOGO,
+                    Some("c"),
+                ),
+                vec![(RawKeyDown, 0x43, COMMAND, 'c' as u16)],
+            ),
+            (
+                "altgr types its character",
+                pressed(
+                    character("q"),
+                    Code::KeyQ,
+                    Standard,
+                    Modifiers::CTRL | Modifiers::ALT,
+                    Some("@"),
+                ),
+                vec![
+                    (RawKeyDown, 0x51, CONTROL | ALT | ALTGR, '@' as u16),
+                    (Char, 0x51, CONTROL | ALT | ALTGR, '@' as u16),
+                ],
+            ),
+            (
+                "altgr key",
+                pressed(
+                    Key::Named(Named::AltGraph),
+                    Code::AltRight,
+                    Right,
+                    none,
+                    None,
+                ),
+                vec![(RawKeyDown, 0x12, RIGHT | ALTGR, 0)],
+            ),
+            (
+                "punctuation",
+                pressed(character(";"), Code::Semicolon, Standard, none, Some(";")),
+                vec![
+                    (RawKeyDown, 0xBA, 0, ';' as u16),
+                    (Char, 0xBA, 0, ';' as u16),
+                ],
+            ),
+            (
+                "non-latin layout falls back to the key position",
+                pressed(character("с"), Code::KeyC, Standard, none, Some("с")),
+                vec![
+                    (RawKeyDown, 0x43, 0, 'с' as u16),
+                    (Char, 0x43, 0, 'с' as u16),
+                ],
+            ),
+            (
+                "enter types a carriage return",
+                pressed(Key::Named(Named::Enter), Code::Enter, Standard, none, None),
+                vec![
+                    (RawKeyDown, 0x0D, 0, '\r' as u16),
+                    (Char, 0x0D, 0, '\r' as u16),
+                ],
+            ),
+            (
+                "backspace types nothing",
+                pressed(
+                    Key::Named(Named::Backspace),
+                    Code::Backspace,
+                    Standard,
+                    none,
+                    Some("\u{8}"),
+                ),
+                vec![(RawKeyDown, 0x08, 0, 0x08)],
+            ),
+            (
+                "arrow",
+                pressed(
+                    Key::Named(Named::ArrowLeft),
+                    Code::ArrowLeft,
+                    Standard,
+                    none,
+                    None,
+                ),
+                vec![(RawKeyDown, 0x25, 0, 0)],
+            ),
+            (
+                "keypad digit",
+                pressed(character("7"), Code::Numpad7, Numpad, none, Some("7")),
+                vec![
+                    (RawKeyDown, 0x67, KEYPAD, '7' as u16),
+                    (Char, 0x67, KEYPAD, '7' as u16),
+                ],
+            ),
+            (
+                "keypad without num lock",
+                pressed(Key::Named(Named::Home), Code::Numpad7, Numpad, none, None),
+                vec![(RawKeyDown, 0x24, KEYPAD, 0)],
+            ),
+            (
+                "left shift",
+                pressed(
+                    Key::Named(Named::Shift),
+                    Code::ShiftLeft,
+                    Left,
+                    Modifiers::SHIFT,
+                    None,
+                ),
+                vec![(RawKeyDown, 0x10, SHIFT | LEFT, 0)],
+            ),
+            (
+                "right super",
+                pressed(
+                    Key::Named(Named::Super),
+                    Code::SuperRight,
+                    Right,
+                    Modifiers::LOGO,
+                    None,
+                ),
+                vec![(RawKeyDown, 0x5C, COMMAND | RIGHT, 0)],
+            ),
+            (
+                "dead key types nothing until the next key",
+                pressed(Key::Unidentified, Code::Quote, Standard, none, None),
+                vec![(RawKeyDown, 0xDE, 0, 0)],
+            ),
+            (
+                "composed character after a dead key",
+                pressed(character("e"), Code::KeyE, Standard, none, Some("é")),
+                vec![
+                    (RawKeyDown, 0x45, 0, 'é' as u16),
+                    (Char, 0x45, 0, 'é' as u16),
+                ],
+            ),
+            (
+                "release",
+                released(character("a"), Code::KeyA, Standard, Modifiers::SHIFT),
+                vec![(KeyUp, 0x41, SHIFT, 0)],
+            ),
+            (
+                "right ctrl release",
+                released(
+                    Key::Named(Named::Control),
+                    Code::ControlRight,
+                    Right,
+                    Modifiers::empty(),
+                ),
+                vec![(KeyUp, 0x11, RIGHT, 0)],
+            ),
+            (
+                "modifiers changed",
+                keyboard::Event::ModifiersChanged(Modifiers::SHIFT),
+                vec![],
+            ),
+        ];
+        for (name, event, expected) in cases {
+            let strokes = translate(&event, Platform::Linux);
+            assert_eq!(summary(&strokes), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn every_utf16_unit_is_a_char() {
+        let event = pressed(
+            Key::Unidentified,
+            Code::KeyA,
+            Location::Standard,
+            Modifiers::empty(),
+            Some("a😀"),
+        );
+        let chars: Vec<u16> = translate(&event, Platform::Linux)
+            .iter()
+            .filter(|stroke| stroke.kind == Char)
+            .map(|stroke| stroke.character)
+            .collect();
+        assert_eq!(chars, "a😀".encode_utf16().collect::<Vec<_>>());
+        assert_eq!(chars.len(), 3);
+    }
+
+    #[test]
+    fn repeat_is_flagged() {
+        let event = keyboard::Event::KeyPressed {
+            key: character("a"),
+            modified_key: character("a"),
+            physical_key: Physical::Code(Code::KeyA),
+            location: Location::Standard,
+            modifiers: Modifiers::empty(),
+            text: Some("a".into()),
+            repeat: true,
+        };
+        for stroke in translate(&event, Platform::Linux) {
+            assert_eq!(stroke.modifiers & REPEAT, REPEAT, "{stroke:?}");
+        }
+    }
+
+    #[test]
+    fn unmodified_character_ignores_shift() {
+        let event = pressed(
+            character("a"),
+            Code::KeyA,
+            Location::Standard,
+            Modifiers::SHIFT,
+            Some("A"),
+        );
+        for stroke in translate(&event, Platform::Linux) {
+            assert_eq!(stroke.unmodified_character, 'a' as u16, "{stroke:?}");
+        }
+    }
+
+    #[test]
+    fn native_key_code_per_platform() {
+        let cases = [
+            (Code::KeyA, Platform::Linux, 38),
+            (Code::KeyA, Platform::MacOs, 0x00),
+            (Code::KeyA, Platform::Windows, 0x001E_0001),
+            (Code::ArrowUp, Platform::Linux, 111),
+            (Code::ArrowUp, Platform::MacOs, 0x7E),
+            (Code::ArrowUp, Platform::Windows, 0x0148_0001),
+            (Code::Escape, Platform::Linux, 9),
+            (Code::Escape, Platform::MacOs, 0x35),
+            (Code::NumpadEnter, Platform::Windows, 0x011C_0001),
+            (Code::ControlRight, Platform::Linux, 105),
+        ];
+        for (code, platform, expected) in cases {
+            let event = pressed(
+                Key::Unidentified,
+                code,
+                Location::Standard,
+                Modifiers::empty(),
+                None,
+            );
+            let strokes = translate(&event, platform);
+            assert_eq!(
+                strokes[0].native_key_code, expected,
+                "{code:?} on {platform:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn windows_conventions() {
+        let event = pressed(
+            character("a"),
+            Code::KeyA,
+            Location::Standard,
+            Modifiers::empty(),
+            Some("a"),
+        );
+        let strokes = translate(&event, Platform::Windows);
+        assert_eq!(strokes[1].kind, Char);
+        assert_eq!(strokes[1].windows_key_code, 'a' as i32);
+
+        let release = released(
+            character("a"),
+            Code::KeyA,
+            Location::Standard,
+            Modifiers::empty(),
+        );
+        let strokes = translate(&release, Platform::Windows);
+        assert_eq!(strokes[0].native_key_code as u32, 0xC01E_0001);
+
+        let alt = pressed(
+            character("f"),
+            Code::KeyF,
+            Location::Standard,
+            Modifiers::ALT,
+            None,
+        );
+        assert!(translate(&alt, Platform::Windows)[0].is_system_key);
+        assert!(!translate(&alt, Platform::Linux)[0].is_system_key);
+    }
+
+    #[test]
+    fn unknown_keys_stay_zero() {
+        let event = keyboard::Event::KeyPressed {
+            key: Key::Unidentified,
+            modified_key: Key::Unidentified,
+            physical_key: Physical::Unidentified(keyboard::key::NativeCode::Unidentified),
+            location: Location::Standard,
+            modifiers: Modifiers::empty(),
+            text: None,
+            repeat: false,
+        };
+        assert_eq!(
+            summary(&translate(&event, Platform::Linux)),
+            vec![(RawKeyDown, 0, 0, 0)]
+        );
+    }
+}
@@ -1,33 +1,68 @@
+mod bindings;
 mod browser;
 mod client;
+mod cookie;
+mod devtools;
 mod error;
+mod eval;
+mod geometry;
 mod instance;
+mod key_event;
+pub mod overlay;
+mod pdf;
+mod profile;
 /// Running in non-browser process
 pub mod render_process;
 mod request;
+mod scheme;
+mod screenshot;
 mod settings;
 mod task;
 mod v8;
 mod webview;
+mod zoom;
 
 use std::time::Duration;
 
 use crate::browser::AppBuilder;
 use crate::browser::IcyBrowserProcessHandler;
 use crate::error::Result;
+pub use bindings::JsBindings;
 pub use browser::IcyCefApp;
 use cef::ImplCommandLine;
+pub use cookie::{Cookie, CookieManager, SameSite};
 use error::CefError;
+pub use error::{CookieError, EvalError, PdfError, ProfileError, ScreenshotError};
 use tokio::sync::mpsc::UnboundedReceiver;
 
 pub use client::ClientEventSubscriber;
+pub use client::DisplayEvent;
+pub use client::FindResult;
 pub use client::IcyClient;
 pub use client::IcyClientState;
 pub use client::LifeSpanEvent;
+pub use client::LoadEvent;
+pub use client::{
+    ContextMenu, ContextMenuEvent, ContextMenuItem, ContextMenuParams, EditFlags, MediaType,
+    MenuCommand,
+};
+pub use client::{DownloadCommand, DownloadEvent, DownloadPolicy};
+pub use client::{FileDialogMode, FileDialogProvider, FileDialogRequest, FileDialogResponse};
+pub use client::{
+    InterceptedRequest, InterceptedResponse, RequestAction, RequestInterceptor, ResourceType,
+};
+pub use client::{JsDialog, JsDialogEvent, JsDialogKind};
+pub use client::{PopupAction, PopupFeatures, PopupRequest, PopupWebview, WindowDisposition};
 pub use instance::CefAction;
 pub use instance::CefComponent;
 pub use instance::CefMessage;
+pub use pdf::{PdfMargins, PdfOptions, PdfPageSize};
+pub use profile::Profile;
+pub use scheme::{CustomScheme, SchemeHandler, SchemeRequest, SchemeResponse, register_scheme};
+pub use screenshot::{Screenshot, ScreenshotArea};
+pub use settings::{CefSettings, CommandLineSwitches, LogSeverity};
 pub use webview::Webview;
+pub use zoom::ZOOM_FACTORS;
 
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Eq, Hash, Ord)]
 pub struct BrowserId(i32);
@@ -68,17 +103,26 @@ pub enum BrowserProcessMessage {
     Tick(Duration),
 }
 
+/// Initialize cef with default [`CefSettings`]
 pub fn init_cef() -> Result<Option<(IcyCefApp, UnboundedReceiver<BrowserProcessMessage>)>> {
+    init_cef_with(CefSettings::new())
+}
+
+/// Initialize cef, returns `None` in non-browser processes which have already run to exit
+pub fn init_cef_with(
+    settings: CefSettings,
+) -> Result<Option<(IcyCefApp, UnboundedReceiver<BrowserProcessMessage>)>> {
     let args = cef::args::Args::new();
     let Some(cmd) = args.as_cmd_line() else {
         return Err(CefError::Custom("cannot get cmd line".into()));
     };
     let is_browser_process = cmd.has_switch(Some(&cef::CefString::from("type"))) != 1;
+    let schemes = scheme::registered_schemes();
 
     if !is_browser_process {
         let ret = cef::execute_process(
             Some(args.as_main_args()),
-            Some(&mut render_process::RenderApp::new()),
+            Some(&mut render_process::RenderApp::new(schemes)),
             std::ptr::null_mut(),
         );
         if ret < 0 {
@@ -91,7 +135,12 @@ pub fn init_cef() -> Result<Option<(IcyCefApp, UnboundedReceiver<BrowserProcessM
 
     let (browser_handler, rx) = IcyBrowserProcessHandler::new();
     let app = IcyCefApp::new();
-    let mut cef_app = AppBuilder::build(app.clone(), browser_handler);
+    let mut cef_app = AppBuilder::build(
+        app.clone(),
+        browser_handler,
+        schemes.clone(),
+        settings.command_line_switches(),
+    );
     let ret = cef::execute_process(
         Some(args.as_main_args()),
         Some(&mut cef_app),
@@ -101,7 +150,7 @@ pub fn init_cef() -> Result<Option<(IcyCefApp, UnboundedReceiver<BrowserProcessM
         return Err(CefError::ProcessLaunchFailed);
     }
 
-    let settings = settings::CefSettings::new();
+    profile::set_root_cache_path(settings.effective_root_cache_path().clone());
     let ret = cef::initialize(
         Some(args.as_main_args()),
         Some(&settings.into_cef_settings()),
@@ -111,5 +160,6 @@ pub fn init_cef() -> Result<Option<(IcyCefApp, UnboundedReceiver<BrowserProcessM
     if ret != 1 {
         return Err(CefError::CannotInit(ret));
     }
+    scheme::register_scheme_handler_factories(&schemes);
     Ok(Some((app, rx)))
 }
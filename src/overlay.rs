@@ -0,0 +1,7 @@
+//! Ready-made iced overlays for browser UI that offscreen rendering leaves to the embedder
+
+mod context_menu;
+mod js_dialog;
+
+pub use context_menu::context_menu;
+pub use js_dialog::{JsDialogOverlay, js_dialog, js_dialog_prompt_id};
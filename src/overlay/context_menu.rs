@@ -0,0 +1,80 @@
+use iced::{
+    Element, Length, Padding, Point, Size,
+    widget::{button, column, container, mouse_area, responsive, space, text},
+};
+
+use crate::client::{ContextMenu, ContextMenuItem, MenuCommand};
+
+const MENU_WIDTH: f32 = 220.0;
+const MENU_PADDING: f32 = 4.0;
+/// Approximate heights to keep the menu inside the view
+const ITEM_HEIGHT: f32 = 26.0;
+const SEPARATOR_HEIGHT: f32 = 9.0;
+
+/// Renders `menu` at its click position over the whole view, moved left and up
+/// where it would overflow the view.
+///
+/// Choosing an item publishes `on_select(Some(command))`, clicking outside
+/// the menu publishes `on_select(None)`. Stack it on top of the webview.
+pub fn context_menu<'a, Message: Clone + 'a>(
+    menu: &'a ContextMenu,
+    on_select: impl Fn(Option<MenuCommand>) -> Message + 'a,
+) -> Element<'a, Message> {
+    responsive(move |size| {
+        let items = menu.items.iter().map(|item| match item {
+            ContextMenuItem::Separator => {
+                container(container(space().width(Length::Fill).height(1)).style(
+                    |theme: &iced::Theme| container::Style {
+                        background: Some(theme.extended_palette().background.strong.color.into()),
+                        ..Default::default()
+                    },
+                ))
+                .padding([4, 0])
+                .into()
+            }
+            ContextMenuItem::Command {
+                command,
+                label,
+                enabled,
+            } => button(text(label.as_str()).size(14))
+                .width(Length::Fill)
+                .padding([4, 12])
+                .style(button::text)
+                .on_press_maybe(enabled.then(|| on_select(Some(*command))))
+                .into(),
+        });
+        let menu_box = container(column(items).width(MENU_WIDTH))
+            .padding(MENU_PADDING)
+            .style(container::bordered_box);
+        let position = position(menu, size);
+
+        mouse_area(
+            container(menu_box)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .padding(Padding::ZERO.top(position.y).left(position.x)),
+        )
+        .on_press(on_select(None))
+        .on_right_press(on_select(None))
+        .into()
+    })
+    .into()
+}
+
+/// Top left corner of the menu in a view of `size`
+fn position(menu: &ContextMenu, size: Size) -> Point {
+    let height = menu
+        .items
+        .iter()
+        .map(|item| match item {
+            ContextMenuItem::Separator => SEPARATOR_HEIGHT,
+            ContextMenuItem::Command { .. } => ITEM_HEIGHT,
+        })
+        .sum::<f32>()
+        + 2.0 * MENU_PADDING;
+    let width = MENU_WIDTH + 2.0 * MENU_PADDING;
+    Point::new(
+        (menu.params.x as f32).min(size.width - width).max(0.0),
+        (menu.params.y as f32).min(size.height - height).max(0.0),
+    )
+}
@@ -0,0 +1,118 @@
+use iced::{
+    Color, Element, Length, Theme, mouse,
+    widget::{Id, button, column, container, mouse_area, row, space, text, text_input},
+};
+
+use crate::client::{JsDialog, JsDialogKind};
+
+const DIALOG_WIDTH: f32 = 420.0;
+
+/// Id of the text field of a `prompt`, focus it once the dialog opens
+pub fn js_dialog_prompt_id() -> Id {
+    Id::new("iced-webview-js-dialog-prompt")
+}
+
+/// Modal overlay for `alert`, `confirm`, `prompt` and `onbeforeunload`.
+///
+/// `input` is the current prompt text, kept by the caller and updated through
+/// `on_input`. Stack it on top of the webview.
+pub fn js_dialog<'a, Message: Clone + 'a>(
+    dialog: &'a JsDialog,
+    input: &'a str,
+    on_input: impl Fn(String) -> Message + 'a,
+    on_answer: impl Fn(bool, Option<String>) -> Message + 'a,
+) -> JsDialogOverlay<'a, Message> {
+    JsDialogOverlay {
+        dialog,
+        input,
+        on_input: Box::new(on_input),
+        on_answer: Box::new(on_answer),
+        style: Box::new(container::bordered_box),
+    }
+}
+
+pub struct JsDialogOverlay<'a, Message> {
+    dialog: &'a JsDialog,
+    input: &'a str,
+    on_input: Box<dyn Fn(String) -> Message + 'a>,
+    on_answer: Box<dyn Fn(bool, Option<String>) -> Message + 'a>,
+    style: Box<dyn Fn(&Theme) -> container::Style + 'a>,
+}
+
+impl<'a, Message> JsDialogOverlay<'a, Message> {
+    /// Style of the dialog box, [`container::bordered_box`] by default
+    pub fn style(mut self, style: impl Fn(&Theme) -> container::Style + 'a) -> Self {
+        self.style = Box::new(style);
+        self
+    }
+}
+
+impl<'a, Message: Clone + 'a> From<JsDialogOverlay<'a, Message>> for Element<'a, Message> {
+    fn from(overlay: JsDialogOverlay<'a, Message>) -> Self {
+        let JsDialogOverlay {
+            dialog,
+            input,
+            on_input,
+            on_answer,
+            style,
+        } = overlay;
+
+        let (title, accept, cancel) = match &dialog.kind {
+            JsDialogKind::Alert => (dialog.origin_url.as_str(), "OK", None),
+            JsDialogKind::Confirm | JsDialogKind::Prompt { .. } => {
+                (dialog.origin_url.as_str(), "OK", Some("Cancel"))
+            }
+            JsDialogKind::BeforeUnload { is_reload: true } => {
+                ("Reload site?", "Reload", Some("Cancel"))
+            }
+            JsDialogKind::BeforeUnload { is_reload: false } => {
+                ("Leave site?", "Leave", Some("Cancel"))
+            }
+        };
+        let accepted = match &dialog.kind {
+            JsDialogKind::Prompt { .. } => on_answer(true, Some(input.to_string())),
+            _ => on_answer(true, None),
+        };
+
+        let mut content = column![text(title).size(16), text(dialog.message.as_str())].spacing(12);
+        if let JsDialogKind::Prompt { .. } = &dialog.kind {
+            content = content.push(
+                text_input("", input)
+                    .id(js_dialog_prompt_id())
+                    .on_input(on_input)
+                    .on_submit(accepted.clone()),
+            );
+        }
+        let mut buttons = row![space().width(Length::Fill)].spacing(8);
+        if let Some(cancel) = cancel {
+            buttons = buttons.push(
+                button(text(cancel))
+                    .style(button::secondary)
+                    .on_press(on_answer(false, None)),
+            );
+        }
+        buttons = buttons.push(
+            button(text(accept))
+                .style(button::primary)
+                .on_press(accepted),
+        );
+
+        let dialog_box = container(content.push(buttons))
+            .width(DIALOG_WIDTH)
+            .padding(16)
+            .style(style);
+
+        // swallow the cursor so the page underneath stays untouched, keys are held back
+        // by the component while the dialog is pending
+        mouse_area(
+            container(dialog_box)
+                .center(Length::Fill)
+                .style(|_theme: &Theme| container::Style {
+                    background: Some(Color::from_rgba(0.0, 0.0, 0.0, 0.4).into()),
+                    ..Default::default()
+                }),
+        )
+        .interaction(mouse::Interaction::Idle)
+        .into()
+    }
+}
@@ -0,0 +1,260 @@
+//! Printing the page of a webview into a pdf file
+
+use camino::Utf8PathBuf;
+use cef::{
+    self, ImplPdfPrintCallback, PdfPrintCallback, WrapPdfPrintCallback,
+    rc::{Rc, RcImpl},
+    sys,
+};
+use std::{
+    ptr::null_mut,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::oneshot;
+
+/// Paper size of the printed pages, measured in inches
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PdfPageSize {
+    #[default]
+    Letter,
+    Legal,
+    Tabloid,
+    A3,
+    A4,
+    A5,
+    Custom {
+        width: f64,
+        height: f64,
+    },
+}
+
+impl PdfPageSize {
+    /// `(width, height)` in inches, portrait
+    pub fn inches(&self) -> (f64, f64) {
+        match *self {
+            Self::Letter => (8.5, 11.0),
+            Self::Legal => (8.5, 14.0),
+            Self::Tabloid => (11.0, 17.0),
+            Self::A3 => (11.69, 16.54),
+            Self::A4 => (8.27, 11.69),
+            Self::A5 => (5.83, 8.27),
+            Self::Custom { width, height } => (width, height),
+        }
+    }
+}
+
+/// Page margins, measured in inches
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PdfMargins {
+    /// About 0.4 inches on every side
+    #[default]
+    Default,
+    None,
+    Custom {
+        top: f64,
+        right: f64,
+        bottom: f64,
+        left: f64,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PdfOptions {
+    pub page_size: PdfPageSize,
+    pub margins: PdfMargins,
+    pub landscape: bool,
+    /// Print background colors and images
+    pub background_graphics: bool,
+    /// Scale of the page content, `1.0` is 100%
+    pub scale: f64,
+    /// Use the size of css `@page` rules over [`PdfOptions::page_size`]
+    pub prefer_css_page_size: bool,
+    /// Print a header and footer on every page, see [`PdfOptions::header_footer`]
+    pub display_header_footer: bool,
+    /// Html template of the header, chromium's default if `None`
+    pub header_template: Option<String>,
+    /// Html template of the footer, chromium's default if `None`
+    pub footer_template: Option<String>,
+    /// e.g. `1-5, 8, 11-13`, all pages if empty
+    pub page_ranges: String,
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        Self {
+            page_size: PdfPageSize::default(),
+            margins: PdfMargins::default(),
+            landscape: false,
+            background_graphics: false,
+            scale: 1.0,
+            prefer_css_page_size: false,
+            display_header_footer: false,
+            header_template: None,
+            footer_template: None,
+            page_ranges: String::new(),
+        }
+    }
+}
+
+impl PdfOptions {
+    pub fn page_size(mut self, page_size: PdfPageSize) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    pub fn margins(mut self, margins: PdfMargins) -> Self {
+        self.margins = margins;
+        self
+    }
+
+    pub fn landscape(mut self, landscape: bool) -> Self {
+        self.landscape = landscape;
+        self
+    }
+
+    pub fn background_graphics(mut self, background_graphics: bool) -> Self {
+        self.background_graphics = background_graphics;
+        self
+    }
+
+    pub fn scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn prefer_css_page_size(mut self, prefer_css_page_size: bool) -> Self {
+        self.prefer_css_page_size = prefer_css_page_size;
+        self
+    }
+
+    /// Print a header and footer on every page.
+    ///
+    /// The templates are html where elements with the classes `date`, `title`, `url`,
+    /// `pageNumber` and `totalPages` get the values injected, `None` uses the default
+    /// of chromium.
+    pub fn header_footer(mut self, header: Option<String>, footer: Option<String>) -> Self {
+        self.display_header_footer = true;
+        self.header_template = header;
+        self.footer_template = footer;
+        self
+    }
+
+    pub fn page_ranges(mut self, page_ranges: impl Into<String>) -> Self {
+        self.page_ranges = page_ranges.into();
+        self
+    }
+
+    pub(crate) fn to_cef(&self) -> cef::PdfPrintSettings {
+        use sys::cef_pdf_print_margin_type_t;
+
+        let (paper_width, paper_height) = self.page_size.inches();
+        let (margin_type, [margin_top, margin_right, margin_bottom, margin_left]) =
+            match self.margins {
+                PdfMargins::Default => (
+                    cef_pdf_print_margin_type_t::PDF_PRINT_MARGIN_DEFAULT,
+                    [0.0; 4],
+                ),
+                PdfMargins::None => (cef_pdf_print_margin_type_t::PDF_PRINT_MARGIN_NONE, [0.0; 4]),
+                PdfMargins::Custom {
+                    top,
+                    right,
+                    bottom,
+                    left,
+                } => (
+                    cef_pdf_print_margin_type_t::PDF_PRINT_MARGIN_CUSTOM,
+                    [top, right, bottom, left],
+                ),
+            };
+        cef::PdfPrintSettings {
+            landscape: self.landscape as _,
+            print_background: self.background_graphics as _,
+            scale: self.scale,
+            paper_width,
+            paper_height,
+            prefer_css_page_size: self.prefer_css_page_size as _,
+            margin_type: margin_type.into(),
+            margin_top,
+            margin_right,
+            margin_bottom,
+            margin_left,
+            page_ranges: self.page_ranges.as_str().into(),
+            display_header_footer: self.display_header_footer as _,
+            // cef falls back to the default of chromium for empty templates
+            header_template: self.header_template.as_deref().unwrap_or_default().into(),
+            footer_template: self.footer_template.as_deref().unwrap_or_default().into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Print the page of `host` into `path`
+pub(crate) fn print_to_pdf(
+    host: &impl cef::ImplBrowserHost,
+    path: Utf8PathBuf,
+    options: &PdfOptions,
+) -> oneshot::Receiver<bool> {
+    let (tx, rx) = oneshot::channel();
+    let mut callback = PdfPrintCallbackBuilder::build(tx);
+    host.print_to_pdf(
+        Some(&path.as_str().into()),
+        Some(&options.to_cef()),
+        Some(&mut callback),
+    );
+    rx
+}
+
+struct PdfPrintCallbackBuilder {
+    object: *mut RcImpl<sys::_cef_pdf_print_callback_t, Self>,
+    tx: Arc<Mutex<Option<oneshot::Sender<bool>>>>,
+}
+
+impl PdfPrintCallbackBuilder {
+    fn build(tx: oneshot::Sender<bool>) -> PdfPrintCallback {
+        PdfPrintCallback::new(Self {
+            object: null_mut(),
+            tx: Arc::new(Mutex::new(Some(tx))),
+        })
+    }
+}
+
+impl WrapPdfPrintCallback for PdfPrintCallbackBuilder {
+    fn wrap_rc(&mut self, object: *mut RcImpl<sys::_cef_pdf_print_callback_t, Self>) {
+        self.object = object;
+    }
+}
+
+impl Rc for PdfPrintCallbackBuilder {
+    fn as_base(&self) -> &sys::cef_base_ref_counted_t {
+        unsafe {
+            let base = &*self.object;
+            std::mem::transmute(&base.cef_object)
+        }
+    }
+}
+
+impl Clone for PdfPrintCallbackBuilder {
+    fn clone(&self) -> Self {
+        let object = unsafe {
+            let rc_impl = &mut *self.object;
+            rc_impl.interface.add_ref();
+            rc_impl
+        };
+
+        Self {
+            object,
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl ImplPdfPrintCallback for PdfPrintCallbackBuilder {
+    fn get_raw(&self) -> *mut sys::_cef_pdf_print_callback_t {
+        self.object.cast()
+    }
+
+    fn on_pdf_print_finished(&self, _path: Option<&cef::CefString>, ok: ::std::os::raw::c_int) {
+        if let Some(tx) = self.tx.lock().ok().and_then(|mut tx| tx.take()) {
+            _ = tx.send(ok == 1);
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! Request contexts(storage, cache, cookies) shared between webviews
+
+use camino::Utf8PathBuf;
+use std::{
+    cell::{Cell, OnceCell, RefCell},
+    collections::BTreeMap,
+    sync::{OnceLock, mpsc},
+    time::Duration,
+};
+
+use crate::{
+    CookieManager, ProfileError,
+    request::{IcyRequestContextHandler, RequestContextHandlerBuilder},
+    scheme,
+};
+
+/// File in the cache path of a persistent profile holding the zoom factor per host
+const ZOOM_LEVELS_FILE: &str = "icy_zoom_levels.json";
+
+/// Zoom steps within this delay are saved with a single write
+const ZOOM_LEVELS_SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// `root_cache_path` passed to [`crate::init_cef_with`], parent of named profiles
+static ROOT_CACHE_PATH: OnceLock<Utf8PathBuf> = OnceLock::new();
+
+pub(crate) fn set_root_cache_path(path: Utf8PathBuf) {
+    _ = ROOT_CACHE_PATH.set(path);
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ProfileKind {
+    Global,
+    Persistent(Utf8PathBuf),
+    Incognito,
+}
+
+struct ProfileInner {
+    kind: ProfileKind,
+    context: RefCell<Option<cef::RequestContext>>,
+    remember_zoom: Cell<bool>,
+    /// zoom factor per host, loaded on first use
+    zoom_levels: RefCell<Option<BTreeMap<String, f64>>>,
+    /// writes the zoom levels of a persistent profile off the ui thread, started on first use
+    zoom_levels_writer: OnceCell<mpsc::Sender<Vec<u8>>>,
+}
+
+/// Handle to a request context, clones share the same storage.
+///
+/// The context is created lazily with the first webview launched with this profile.
+#[derive(Clone)]
+pub struct Profile {
+    inner: std::rc::Rc<ProfileInner>,
+}
+
+impl std::fmt::Debug for Profile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Profile")
+            .field("kind", &self.inner.kind)
+            .finish()
+    }
+}
+
+impl Profile {
+    fn new(kind: ProfileKind) -> Self {
+        Self {
+            inner: std::rc::Rc::new(ProfileInner {
+                kind,
+                context: RefCell::new(None),
+                remember_zoom: Cell::new(false),
+                zoom_levels: RefCell::new(None),
+                zoom_levels_writer: OnceCell::new(),
+            }),
+        }
+    }
+
+    /// The global context configured by [`crate::CefSettings`]
+    pub fn global() -> Self {
+        Self::new(ProfileKind::Global)
+    }
+
+    /// Persisted under `<root_cache_path>/profiles/<name>`.
+    ///
+    /// `name` must be a single path component, and a root cache path must be configured.
+    pub fn named(name: &str) -> Result<Self, ProfileError> {
+        let mut components = camino::Utf8Path::new(name).components();
+        let single = matches!(
+            (components.next(), components.next()),
+            (Some(camino::Utf8Component::Normal(component)), None) if component == name
+        );
+        if !single || name.contains(['/', '\\', ':']) {
+            return Err(ProfileError::InvalidName(name.to_string()));
+        }
+        let root = ROOT_CACHE_PATH
+            .get()
+            .filter(|root| !root.as_str().is_empty())
+            .ok_or(ProfileError::NoRootCachePath)?;
+        Ok(Self::persistent(root.join("profiles").join(name)))
+    }
+
+    /// Persisted at `path`, which must be a child of the root cache path
+    pub fn persistent(path: impl Into<Utf8PathBuf>) -> Self {
+        Self::new(ProfileKind::Persistent(path.into()))
+    }
+
+    /// In-memory storage dropped with the last webview using it
+    pub fn incognito() -> Self {
+        Self::new(ProfileKind::Incognito)
+    }
+
+    pub fn is_incognito(&self) -> bool {
+        self.inner.kind == ProfileKind::Incognito
+    }
+
+    pub(crate) fn is_global(&self) -> bool {
+        self.inner.kind == ProfileKind::Global
+    }
+
+    pub fn cache_path(&self) -> Option<&camino::Utf8Path> {
+        match &self.inner.kind {
+            ProfileKind::Persistent(path) => Some(path),
+            ProfileKind::Global | ProfileKind::Incognito => None,
+        }
+    }
+
+    /// Restore the zoom of a page from the last visit to its host.
+    ///
+    /// Persistent profiles keep the levels in their cache path, other profiles in memory.
+    pub fn remember_zoom(self, enabled: bool) -> Self {
+        self.inner.remember_zoom.set(enabled);
+        self
+    }
+
+    pub fn remembers_zoom(&self) -> bool {
+        self.inner.remember_zoom.get()
+    }
+
+    /// Zoom factor remembered for `host`
+    pub fn zoom_for_host(&self, host: &str) -> Option<f64> {
+        if !self.remembers_zoom() {
+            return None;
+        }
+        self.with_zoom_levels(|levels| levels.get(host).copied())
+    }
+
+    pub(crate) fn set_zoom_for_host(&self, host: &str, factor: f64) {
+        if !self.remembers_zoom() {
+            return;
+        }
+        let levels = self.with_zoom_levels(|levels| {
+            if (factor - 1.0).abs() < f64::EPSILON {
+                levels.remove(host);
+            } else {
+                levels.insert(host.to_string(), factor);
+            }
+            levels.clone()
+        });
+        if let Some(path) = self.cache_path() {
+            match serde_json::to_vec(&levels) {
+                Ok(json) => {
+                    let writer = self
+                        .inner
+                        .zoom_levels_writer
+                        .get_or_init(|| spawn_zoom_levels_writer(path.join(ZOOM_LEVELS_FILE)));
+                    _ = writer.send(json);
+                }
+                Err(err) => tracing::warn!(?err, "cannot serialize zoom levels"),
+            }
+        }
+    }
+
+    fn with_zoom_levels<T>(&self, f: impl FnOnce(&mut BTreeMap<String, f64>) -> T) -> T {
+        let mut levels = self.inner.zoom_levels.borrow_mut();
+        let levels = levels.get_or_insert_with(|| {
+            self.cache_path()
+                .and_then(|path| std::fs::read(path.join(ZOOM_LEVELS_FILE)).ok())
+                .and_then(|json| serde_json::from_slice(&json).ok())
+                .unwrap_or_default()
+        });
+        f(levels)
+    }
+
+    /// Cookies of this profile, shared by all webviews created with it
+    pub fn cookie_manager(&self) -> Option<CookieManager> {
+        self.request_context()
+            .and_then(|context| CookieManager::new(&context))
+    }
+
+    pub(crate) fn request_context(&self) -> Option<cef::RequestContext> {
+        let mut context = self.inner.context.borrow_mut();
+        if context.is_none() {
+            *context = match &self.inner.kind {
+                ProfileKind::Global => cef::request_context_get_global_context(),
+                ProfileKind::Persistent(path) => {
+                    Self::create_context(cef::RequestContextSettings {
+                        cache_path: path.as_str().into(),
+                        persist_session_cookies: true as _,
+                        ..Default::default()
+                    })
+                }
+                ProfileKind::Incognito => {
+                    Self::create_context(cef::RequestContextSettings::default())
+                }
+            };
+        }
+        context.clone()
+    }
+
+    fn create_context(settings: cef::RequestContextSettings) -> Option<cef::RequestContext> {
+        let context = cef::request_context_create_context(
+            Some(&settings),
+            Some(&mut RequestContextHandlerBuilder::build(
+                IcyRequestContextHandler {},
+            )),
+        )?;
+        scheme::register_context_scheme_handler_factories(&context);
+        Some(context)
+    }
+}
+
+/// Save the latest zoom levels sent to the returned channel into `path`, the thread ends
+/// with the profile once pending levels are written
+fn spawn_zoom_levels_writer(path: Utf8PathBuf) -> mpsc::Sender<Vec<u8>> {
+    let (tx, rx) = mpsc::channel::<Vec<u8>>();
+    std::thread::spawn(move || {
+        while let Ok(mut json) = rx.recv() {
+            std::thread::sleep(ZOOM_LEVELS_SAVE_DELAY);
+            while let Ok(newer) = rx.try_recv() {
+                json = newer;
+            }
+            if let Err(err) = std::fs::write(&path, json) {
+                tracing::warn!(?err, %path, "cannot save zoom levels");
+            }
+        }
+    });
+    tx
+}
@@ -1,15 +1,30 @@
 use cef::{rc::*, *};
+use std::{cell::RefCell, collections::BTreeMap, sync::atomic::AtomicI32};
 
-use crate::v8::IcyV8HandlerBuilder;
+use crate::{
+    bindings::{BINDINGS_KEY, BindingsConfig, INVOKE_REQUEST, INVOKE_RESULT},
+    eval::{EVAL_REQUEST, EVAL_RESULT},
+    scheme::{CustomScheme, add_custom_schemes},
+    v8::IcyV8HandlerBuilder,
+};
+
+thread_local! {
+    static BINDINGS: RefCell<BTreeMap<i32, BindingsConfig>> = RefCell::new(BTreeMap::new());
+    static PENDING_CALLS: RefCell<BTreeMap<i32, (V8Context, V8Value)>> = RefCell::new(BTreeMap::new());
+    /// evaluations waiting for their promise, by eval id
+    static PENDING_EVALS: RefCell<BTreeMap<i32, V8Context>> = RefCell::new(BTreeMap::new());
+}
 
 pub struct RenderApp {
     object: *mut RcImpl<sys::_cef_app_t, Self>,
+    schemes: Vec<CustomScheme>,
 }
 
 impl RenderApp {
-    pub fn new() -> App {
+    pub fn new(schemes: Vec<CustomScheme>) -> App {
         App::new(Self {
             object: std::ptr::null_mut(),
+            schemes,
         })
     }
 }
@@ -28,7 +43,10 @@ impl Clone for RenderApp {
             self.object
         };
 
-        Self { object }
+        Self {
+            object,
+            schemes: self.schemes.clone(),
+        }
     }
 }
 
@@ -46,6 +64,12 @@ impl ImplApp for RenderApp {
         self.object as *mut sys::_cef_app_t
     }
 
+    fn on_register_custom_schemes(&self, registrar: Option<&mut SchemeRegistrar>) {
+        if let Some(registrar) = registrar {
+            add_custom_schemes(registrar, &self.schemes);
+        }
+    }
+
     fn render_process_handler(&self) -> Option<RenderProcessHandler> {
         Some(IcyRenderProcessHandlerBuilder::build(
             IcyRenderProcessHandler {},
@@ -108,10 +132,30 @@ impl ImplRenderProcessHandler for IcyRenderProcessHandlerBuilder {
     fn on_browser_created(
         &self,
         browser: Option<&mut Browser>,
-        _extra_info: Option<&mut DictionaryValue>,
+        extra_info: Option<&mut DictionaryValue>,
     ) {
+        let Some(browser) = browser else {
+            return;
+        };
+        eprintln!("render: create browser {}", browser.identifier());
+        let Some(extra_info) = extra_info else {
+            return;
+        };
+        if extra_info.has_key(Some(&BINDINGS_KEY.into())) != 1 {
+            return;
+        }
+        let config = CefString::from(&extra_info.string(Some(&BINDINGS_KEY.into()))).to_string();
+        match serde_json::from_str::<BindingsConfig>(&config) {
+            Ok(config) => {
+                BINDINGS.with_borrow_mut(|bindings| bindings.insert(browser.identifier(), config));
+            }
+            Err(err) => tracing::error!(?err, "invalid bindings config"),
+        }
+    }
+
+    fn on_browser_destroyed(&self, browser: Option<&mut Browser>) {
         if let Some(browser) = browser {
-            eprintln!("render: create browser {}", browser.identifier());
+            BINDINGS.with_borrow_mut(|bindings| bindings.remove(&browser.identifier()));
         }
     }
 
@@ -139,8 +183,13 @@ impl ImplRenderProcessHandler for IcyRenderProcessHandlerBuilder {
             let Some(args) = message.argument_list() else {
                 return;
             };
-            let element =
-                    serde_json::to_string(&serde_json::json!({ "x": bound.x, "y": bound.y, "width": bound.height, "height": bound.width })).ok();
+            let element = serde_json::to_string(&serde_json::json!({
+                "x": bound.x,
+                "y": bound.y,
+                "width": bound.width,
+                "height": bound.height,
+            }))
+            .ok();
             if args.set_string(
                 0,
                 element
@@ -165,8 +214,15 @@ impl ImplRenderProcessHandler for IcyRenderProcessHandlerBuilder {
         frame: Option<&mut Frame>,
         context: Option<&mut V8Context>,
     ) {
-        if let Some(browser) = browser {
-            eprintln!("render: context created {}", browser.identifier());
+        let browser_id = browser.map(|browser| browser.identifier());
+        let frame_url = frame.as_ref().map(|frame| {
+            (
+                CefString::from(&frame.url()).to_string(),
+                frame.is_main() == 1,
+            )
+        });
+        if let Some(browser_id) = browser_id {
+            eprintln!("render: context created {browser_id}");
         }
         if let Some(frame) = frame {
             frame.execute_java_script(
@@ -265,16 +321,265 @@ window.addEventListener('keyup', (event) => {
             );
             context.exit();
         }
+
+        if let Some(browser_id) = browser_id {
+            BINDINGS.with_borrow(|bindings| {
+                if let Some(config) = bindings.get(&browser_id).filter(|config| {
+                    frame_url
+                        .as_ref()
+                        .is_some_and(|(url, is_main)| config.allows(url, *is_main))
+                }) {
+                    install_bindings(context, config);
+                }
+            });
+        }
     }
 
     fn on_context_released(
         &self,
         browser: Option<&mut Browser>,
-        _frame: Option<&mut Frame>,
-        _context: Option<&mut V8Context>,
+        frame: Option<&mut Frame>,
+        context: Option<&mut V8Context>,
     ) {
         if let Some(browser) = browser {
             eprintln!("render: context released {}", browser.identifier());
         }
+        if let Some(context) = context {
+            PENDING_CALLS.with_borrow_mut(|calls| {
+                calls.retain(|_, (pending, _)| pending.is_same(Some(context)) != 1)
+            });
+            let cancelled = PENDING_EVALS.with_borrow_mut(|evals| {
+                let cancelled = evals
+                    .iter()
+                    .filter(|(_, pending)| pending.is_same(Some(context)) == 1)
+                    .map(|(id, _)| *id)
+                    .collect::<Vec<_>>();
+                evals.retain(|id, _| !cancelled.contains(id));
+                cancelled
+            });
+            if let Some(frame) = frame {
+                for id in cancelled {
+                    reply_eval(frame, id, r#""cancelled""#);
+                }
+            }
+        }
+    }
+
+    fn on_process_message_received(
+        &self,
+        _browser: Option<&mut Browser>,
+        frame: Option<&mut Frame>,
+        _source_process: ProcessId,
+        message: Option<&mut ProcessMessage>,
+    ) -> ::std::os::raw::c_int {
+        let Some(frame) = frame else {
+            return false as _;
+        };
+        let Some(message) = message else {
+            return false as _;
+        };
+        let name = CefString::from(&message.name()).to_string();
+        if name == INVOKE_RESULT {
+            resolve_binding_call(message);
+            return true as _;
+        }
+        if name != EVAL_REQUEST {
+            return false as _;
+        }
+        let Some(args) = message.argument_list() else {
+            return false as _;
+        };
+        let id = args.int(0);
+        let code = CefString::from(&args.string(1));
+
+        let error = |message: String| {
+            serde_json::json!({ "error": { "message": message, "stack": null } }).to_string()
+        };
+        let Some(context) = frame.v8_context() else {
+            reply_eval(frame, id, &error("no javascript context".to_string()));
+            return true as _;
+        };
+        let mut retval = None;
+        let mut exception = None;
+        context.enter();
+        let evaluated = context.eval(
+            Some(&code),
+            None,
+            0,
+            Some(&mut retval),
+            Some(&mut exception),
+        ) == 1;
+        // the wrapped script evaluates to a promise of the json result
+        let then = retval
+            .as_ref()
+            .filter(|_| evaluated)
+            .and_then(|promise| promise.value_bykey(Some(&"then".into())))
+            .filter(|then| then.is_function() == 1);
+        match (retval, then) {
+            (Some(mut promise), Some(then)) => {
+                PENDING_EVALS.with_borrow_mut(|evals| evals.insert(id, context.clone()));
+                let settled = frame.clone();
+                let mut handler = IcyV8HandlerBuilder::build(move |_name, _this, args| {
+                    let result = match args {
+                        [Some(value), ..] if value.is_string() == 1 => {
+                            CefString::from(&value.string_value()).to_string()
+                        }
+                        _ => error("evaluation did not return a string".to_string()),
+                    };
+                    if PENDING_EVALS
+                        .with_borrow_mut(|evals| evals.remove(&id))
+                        .is_some()
+                    {
+                        reply_eval(&settled, id, &result);
+                    }
+                    cef::v8_value_create_undefined()
+                        .ok_or_else(|| anyhow::anyhow!("cannot create v8 value"))
+                });
+                let callback = cef::v8_value_create_function(
+                    Some(&"__icy_eval_settled".into()),
+                    Some(&mut handler),
+                );
+                if then
+                    .execute_function(Some(&mut promise), Some(&[callback]))
+                    .is_none()
+                {
+                    PENDING_EVALS.with_borrow_mut(|evals| evals.remove(&id));
+                    reply_eval(frame, id, &error("cannot await evaluation".to_string()));
+                }
+            }
+            _ => {
+                let message = exception
+                    .map(|exception| CefString::from(&exception.message()).to_string())
+                    .unwrap_or_else(|| "cannot evaluate script".to_string());
+                reply_eval(frame, id, &error(message));
+            }
+        }
+        context.exit();
+        true as _
+    }
+}
+
+/// Send the json `result` of evaluation `id` back to the browser process
+fn reply_eval(frame: &Frame, id: i32, result: &str) {
+    let Some(mut reply) = cef::process_message_create(Some(&EVAL_RESULT.into())) else {
+        return;
+    };
+    let Some(reply_args) = reply.argument_list() else {
+        return;
+    };
+    if reply_args.set_int(0, id) != 1 || reply_args.set_string(1, Some(&result.into())) != 1 {
+        return;
+    }
+    frame.send_process_message(
+        cef::sys::cef_process_id_t::PID_BROWSER.into(),
+        Some(&mut reply),
+    );
+}
+
+/// Install a native `__icy_invoke(name, argsJson)` returning a promise, and wrap it
+/// with one function per binding on `window[namespace]`.
+fn install_bindings(context: &mut V8Context, config: &BindingsConfig) {
+    let mut invoke_handler = IcyV8HandlerBuilder::build(|_name, _this, args| {
+        let [Some(name), Some(payload)] = args else {
+            anyhow::bail!("expect binding name and arguments");
+        };
+        let Some(context) = cef::v8_context_get_current_context() else {
+            anyhow::bail!("no v8 context")
+        };
+        let Some(frame) = context.frame() else {
+            anyhow::bail!("no frame")
+        };
+        let Some(promise) = cef::v8_value_create_promise() else {
+            anyhow::bail!("cannot create promise");
+        };
+
+        let Some(mut message) = cef::process_message_create(Some(&INVOKE_REQUEST.into())) else {
+            anyhow::bail!("cannot create ipc message");
+        };
+        let Some(message_args) = message.argument_list() else {
+            anyhow::bail!("no args");
+        };
+        static NEXT_CALL_ID: AtomicI32 = AtomicI32::new(0);
+        let call_id = NEXT_CALL_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        if message_args.set_int(0, call_id) != 1
+            || message_args.set_string(1, Some(&CefString::from(&name.string_value()))) != 1
+            || message_args.set_string(2, Some(&CefString::from(&payload.string_value()))) != 1
+        {
+            anyhow::bail!("cannot set payload");
+        }
+
+        PENDING_CALLS.with_borrow_mut(|calls| calls.insert(call_id, (context, promise.clone())));
+        frame.send_process_message(
+            cef::sys::cef_process_id_t::PID_BROWSER.into(),
+            Some(&mut message),
+        );
+        Ok(promise)
+    });
+
+    let Some(global) = context.global() else {
+        return;
+    };
+    context.enter();
+    if let Some(mut invoke) =
+        cef::v8_value_create_function(Some(&"__icy_invoke".into()), Some(&mut invoke_handler))
+    {
+        global.set_value_bykey(
+            Some(&"__icy_invoke".into()),
+            Some(&mut invoke),
+            cef::sys::cef_v8_propertyattribute_t::V8_PROPERTY_ATTRIBUTE_READONLY.into(),
+        );
+    }
+    context.exit();
+
+    let (Ok(namespace), Ok(names)) = (
+        serde_json::to_string(&config.namespace),
+        serde_json::to_string(&config.names),
+    ) else {
+        return;
+    };
+    let code = format!(
+        r#"(() => {{
+  const invoke = window.__icy_invoke;
+  const namespace = (window[{namespace}] = window[{namespace}] || {{}});
+  for (const name of {names}) {{
+    namespace[name] = (...args) =>
+      invoke(name, JSON.stringify(args)).then((result) => JSON.parse(result));
+  }}
+}})()"#
+    );
+    let mut retval = None;
+    let mut exception = None;
+    if context.eval(
+        Some(&code.as_str().into()),
+        None,
+        0,
+        Some(&mut retval),
+        Some(&mut exception),
+    ) != 1
+    {
+        tracing::error!("cannot install bindings");
+    }
+}
+
+fn resolve_binding_call(message: &mut ProcessMessage) {
+    let Some(args) = message.argument_list() else {
+        return;
+    };
+    let call_id = args.int(0);
+    let success = args.bool(1) == 1;
+    let payload = CefString::from(&args.string(2));
+    let Some((context, promise)) = PENDING_CALLS.with_borrow_mut(|calls| calls.remove(&call_id))
+    else {
+        return;
+    };
+
+    context.enter();
+    if success {
+        if let Some(mut value) = cef::v8_value_create_string(Some(&payload)) {
+            promise.resolve_promise(Some(&mut value));
+        }
+    } else {
+        promise.reject_promise(Some(&payload));
     }
+    context.exit();
 }
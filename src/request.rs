@@ -99,3 +99,23 @@ impl ImplRequestContextHandler for RequestContextHandlerBuilder {
         }
     }
 }
+
+pub(crate) fn request_headers(request: &cef::Request) -> Vec<(String, String)> {
+    let mut headers = cef::CefStringMultimap::new();
+    request.header_map(Some(&mut headers));
+    Vec::from(&headers)
+}
+
+pub(crate) fn set_request_headers(request: &cef::Request, headers: &[(String, String)]) {
+    let mut header_map = cef::CefStringMultimap::new();
+    for (name, value) in headers {
+        header_map.append(name.as_str(), value.as_str());
+    }
+    request.set_header_map(Some(&mut header_map));
+}
+
+pub(crate) fn response_headers(response: &cef::Response) -> Vec<(String, String)> {
+    let mut headers = cef::CefStringMultimap::new();
+    response.header_map(Some(&mut headers));
+    Vec::from(&headers)
+}
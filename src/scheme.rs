@@ -0,0 +1,466 @@
+//! Custom schemes(e.g. `app://`) served from Rust
+
+use cef::{
+    self, ImplRequest, ImplRequestContext, ImplResourceHandler, ImplResponse,
+    ImplSchemeHandlerFactory, ImplSchemeRegistrar, ResourceHandler, SchemeHandlerFactory,
+    WrapResourceHandler, WrapSchemeHandlerFactory,
+    rc::{Rc, RcImpl},
+    sys,
+};
+use std::{
+    io::Read,
+    ptr::null_mut,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use crate::request::request_headers;
+
+static SCHEMES: LazyLock<Mutex<Vec<CustomScheme>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+/// Register a custom scheme, must be called in every process before [`crate::init_cef`].
+pub fn register_scheme(scheme: CustomScheme) {
+    if let Ok(mut schemes) = SCHEMES.lock() {
+        schemes.retain(|s| s.name != scheme.name);
+        schemes.push(scheme);
+    }
+}
+
+pub(crate) fn registered_schemes() -> Vec<CustomScheme> {
+    SCHEMES
+        .lock()
+        .map(|schemes| schemes.clone())
+        .unwrap_or_default()
+}
+
+#[derive(Debug)]
+pub struct SchemeRequest {
+    pub url: String,
+    pub method: String,
+    pub headers: Vec<(String, String)>,
+}
+
+pub struct SchemeResponse {
+    pub status: u16,
+    pub status_text: String,
+    pub mime_type: String,
+    pub headers: Vec<(String, String)>,
+    pub content_length: Option<u64>,
+    pub body: Box<dyn Read + Send>,
+}
+
+impl SchemeResponse {
+    pub fn new(
+        status: u16,
+        mime_type: impl Into<String>,
+        body: impl Read + Send + 'static,
+    ) -> Self {
+        Self {
+            status,
+            status_text: String::new(),
+            mime_type: mime_type.into(),
+            headers: Vec::new(),
+            content_length: None,
+            body: Box::new(body),
+        }
+    }
+
+    pub fn ok(mime_type: impl Into<String>, body: impl Into<Vec<u8>>) -> Self {
+        let body = body.into();
+        let content_length = body.len() as u64;
+        Self::new(200, mime_type, std::io::Cursor::new(body)).content_length(content_length)
+    }
+
+    pub fn not_found() -> Self {
+        Self::new(404, "text/plain", std::io::empty()).status_text("Not Found")
+    }
+
+    pub fn status_text(mut self, status_text: impl Into<String>) -> Self {
+        self.status_text = status_text.into();
+        self
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn content_length(mut self, content_length: u64) -> Self {
+        self.content_length = Some(content_length);
+        self
+    }
+}
+
+impl std::fmt::Debug for SchemeResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SchemeResponse")
+            .field("status", &self.status)
+            .field("mime_type", &self.mime_type)
+            .field("headers", &self.headers)
+            .field("content_length", &self.content_length)
+            .finish()
+    }
+}
+
+/// Serve requests of a custom scheme, called on the cef IO thread.
+pub trait SchemeHandler: Send + Sync + 'static {
+    fn handle(&self, request: &SchemeRequest) -> SchemeResponse;
+}
+
+impl<F> SchemeHandler for F
+where
+    F: Fn(&SchemeRequest) -> SchemeResponse + Send + Sync + 'static,
+{
+    fn handle(&self, request: &SchemeRequest) -> SchemeResponse {
+        (self)(request)
+    }
+}
+
+#[derive(Clone)]
+pub struct CustomScheme {
+    name: String,
+    standard: bool,
+    local: bool,
+    display_isolated: bool,
+    secure: bool,
+    cors_enabled: bool,
+    csp_bypassing: bool,
+    fetch_enabled: bool,
+    handler: Option<Arc<dyn SchemeHandler>>,
+}
+
+impl std::fmt::Debug for CustomScheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomScheme")
+            .field("name", &self.name)
+            .field("standard", &self.standard)
+            .field("secure", &self.secure)
+            .field("cors_enabled", &self.cors_enabled)
+            .field("fetch_enabled", &self.fetch_enabled)
+            .finish()
+    }
+}
+
+impl CustomScheme {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            standard: true,
+            local: false,
+            display_isolated: false,
+            secure: true,
+            cors_enabled: true,
+            csp_bypassing: false,
+            fetch_enabled: true,
+            handler: None,
+        }
+    }
+
+    pub fn standard(mut self, standard: bool) -> Self {
+        self.standard = standard;
+        self
+    }
+
+    pub fn local(mut self, local: bool) -> Self {
+        self.local = local;
+        self
+    }
+
+    pub fn display_isolated(mut self, display_isolated: bool) -> Self {
+        self.display_isolated = display_isolated;
+        self
+    }
+
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    pub fn cors_enabled(mut self, cors_enabled: bool) -> Self {
+        self.cors_enabled = cors_enabled;
+        self
+    }
+
+    pub fn csp_bypassing(mut self, csp_bypassing: bool) -> Self {
+        self.csp_bypassing = csp_bypassing;
+        self
+    }
+
+    pub fn fetch_enabled(mut self, fetch_enabled: bool) -> Self {
+        self.fetch_enabled = fetch_enabled;
+        self
+    }
+
+    pub fn handler(mut self, handler: impl SchemeHandler) -> Self {
+        self.handler = Some(Arc::new(handler));
+        self
+    }
+
+    fn options(&self) -> i32 {
+        use sys::cef_scheme_options_t as O;
+        [
+            (self.standard, O::CEF_SCHEME_OPTION_STANDARD),
+            (self.local, O::CEF_SCHEME_OPTION_LOCAL),
+            (self.display_isolated, O::CEF_SCHEME_OPTION_DISPLAY_ISOLATED),
+            (self.secure, O::CEF_SCHEME_OPTION_SECURE),
+            (self.cors_enabled, O::CEF_SCHEME_OPTION_CORS_ENABLED),
+            (self.csp_bypassing, O::CEF_SCHEME_OPTION_CSP_BYPASSING),
+            (self.fetch_enabled, O::CEF_SCHEME_OPTION_FETCH_ENABLED),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .fold(O::CEF_SCHEME_OPTION_NONE.0, |options, (_, option)| {
+            options | option.0
+        }) as _
+    }
+}
+
+pub(crate) fn add_custom_schemes(registrar: &mut cef::SchemeRegistrar, schemes: &[CustomScheme]) {
+    for scheme in schemes {
+        if registrar.add_custom_scheme(Some(&scheme.name.as_str().into()), scheme.options()) != 1 {
+            tracing::error!(scheme = scheme.name, "cannot register custom scheme");
+        }
+    }
+}
+
+/// Serve the schemes of the global request context
+pub(crate) fn register_scheme_handler_factories(schemes: &[CustomScheme]) {
+    register_factories(schemes, |name, factory| {
+        cef::register_scheme_handler_factory(Some(name), None, Some(factory))
+    });
+}
+
+/// Serve the registered schemes of a request context created for a profile,
+/// contexts other than the global one do not see the global factories
+pub(crate) fn register_context_scheme_handler_factories(context: &impl ImplRequestContext) {
+    register_factories(&registered_schemes(), |name, factory| {
+        context.register_scheme_handler_factory(Some(name), None, Some(factory))
+    });
+}
+
+fn register_factories(
+    schemes: &[CustomScheme],
+    register: impl Fn(&cef::CefString, &mut SchemeHandlerFactory) -> std::ffi::c_int,
+) {
+    for scheme in schemes {
+        let Some(handler) = scheme.handler.clone() else {
+            continue;
+        };
+        let mut factory = SchemeHandlerFactoryBuilder::build(handler);
+        if register(&scheme.name.as_str().into(), &mut factory) != 1 {
+            tracing::error!(scheme = scheme.name, "cannot register scheme handler");
+        }
+    }
+}
+
+struct SchemeHandlerFactoryBuilder {
+    object: *mut RcImpl<sys::_cef_scheme_handler_factory_t, Self>,
+    handler: Arc<dyn SchemeHandler>,
+}
+
+impl SchemeHandlerFactoryBuilder {
+    fn build(handler: Arc<dyn SchemeHandler>) -> SchemeHandlerFactory {
+        SchemeHandlerFactory::new(Self {
+            object: null_mut(),
+            handler,
+        })
+    }
+}
+
+impl WrapSchemeHandlerFactory for SchemeHandlerFactoryBuilder {
+    fn wrap_rc(&mut self, object: *mut RcImpl<sys::_cef_scheme_handler_factory_t, Self>) {
+        self.object = object;
+    }
+}
+
+impl Rc for SchemeHandlerFactoryBuilder {
+    fn as_base(&self) -> &sys::cef_base_ref_counted_t {
+        unsafe {
+            let base = &*self.object;
+            std::mem::transmute(&base.cef_object)
+        }
+    }
+}
+
+impl Clone for SchemeHandlerFactoryBuilder {
+    fn clone(&self) -> Self {
+        let object = unsafe {
+            let rc_impl = &mut *self.object;
+            rc_impl.interface.add_ref();
+            rc_impl
+        };
+
+        Self {
+            object,
+            handler: self.handler.clone(),
+        }
+    }
+}
+
+impl ImplSchemeHandlerFactory for SchemeHandlerFactoryBuilder {
+    fn get_raw(&self) -> *mut sys::_cef_scheme_handler_factory_t {
+        self.object.cast()
+    }
+
+    fn create(
+        &self,
+        _browser: Option<&mut cef::Browser>,
+        _frame: Option<&mut cef::Frame>,
+        _scheme_name: Option<&cef::CefString>,
+        _request: Option<&mut cef::Request>,
+    ) -> Option<ResourceHandler> {
+        Some(ResourceHandlerBuilder::build(self.handler.clone()))
+    }
+}
+
+struct ResourceHandlerBuilder {
+    object: *mut RcImpl<sys::_cef_resource_handler_t, Self>,
+    handler: Arc<dyn SchemeHandler>,
+    response: Arc<Mutex<Option<SchemeResponse>>>,
+}
+
+impl ResourceHandlerBuilder {
+    fn build(handler: Arc<dyn SchemeHandler>) -> ResourceHandler {
+        ResourceHandler::new(Self {
+            object: null_mut(),
+            handler,
+            response: Arc::new(Mutex::new(None)),
+        })
+    }
+}
+
+impl WrapResourceHandler for ResourceHandlerBuilder {
+    fn wrap_rc(&mut self, object: *mut RcImpl<sys::_cef_resource_handler_t, Self>) {
+        self.object = object;
+    }
+}
+
+impl Rc for ResourceHandlerBuilder {
+    fn as_base(&self) -> &sys::cef_base_ref_counted_t {
+        unsafe {
+            let base = &*self.object;
+            std::mem::transmute(&base.cef_object)
+        }
+    }
+}
+
+impl Clone for ResourceHandlerBuilder {
+    fn clone(&self) -> Self {
+        let object = unsafe {
+            let rc_impl = &mut *self.object;
+            rc_impl.interface.add_ref();
+            rc_impl
+        };
+
+        Self {
+            object,
+            handler: self.handler.clone(),
+            response: self.response.clone(),
+        }
+    }
+}
+
+impl ImplResourceHandler for ResourceHandlerBuilder {
+    fn get_raw(&self) -> *mut sys::_cef_resource_handler_t {
+        self.object.cast()
+    }
+
+    fn open(
+        &self,
+        request: Option<&mut cef::Request>,
+        handle_request: Option<&mut ::std::os::raw::c_int>,
+        _callback: Option<&mut cef::Callback>,
+    ) -> ::std::os::raw::c_int {
+        let Some(request) = request else {
+            return false as _;
+        };
+        let request = SchemeRequest {
+            url: cef::CefString::from(&request.url()).to_string(),
+            method: cef::CefString::from(&request.method()).to_string(),
+            headers: request_headers(request),
+        };
+        let response = self.handler.handle(&request);
+        tracing::debug!(url = request.url, ?response, "serve custom scheme");
+        if let Ok(mut slot) = self.response.lock() {
+            slot.replace(response);
+        }
+        if let Some(handle_request) = handle_request {
+            *handle_request = true as _;
+        }
+        true as _
+    }
+
+    fn response_headers(
+        &self,
+        response: Option<&mut cef::Response>,
+        response_length: Option<&mut i64>,
+        _redirect_url: Option<&mut cef::CefString>,
+    ) {
+        let Ok(slot) = self.response.lock() else {
+            return;
+        };
+        let Some(scheme_response) = slot.as_ref() else {
+            return;
+        };
+        if let Some(response) = response {
+            response.set_status(scheme_response.status as _);
+            if !scheme_response.status_text.is_empty() {
+                response.set_status_text(Some(&scheme_response.status_text.as_str().into()));
+            }
+            response.set_mime_type(Some(&scheme_response.mime_type.as_str().into()));
+            for (name, value) in &scheme_response.headers {
+                response.set_header_by_name(
+                    Some(&name.as_str().into()),
+                    Some(&value.as_str().into()),
+                    true as _,
+                );
+            }
+        }
+        if let Some(response_length) = response_length {
+            *response_length = scheme_response
+                .content_length
+                .map(|length| length as i64)
+                .unwrap_or(-1);
+        }
+    }
+
+    fn read(
+        &self,
+        data_out: *mut u8,
+        bytes_to_read: ::std::os::raw::c_int,
+        bytes_read: Option<&mut ::std::os::raw::c_int>,
+        _callback: Option<&mut cef::ResourceReadCallback>,
+    ) -> ::std::os::raw::c_int {
+        let Some(bytes_read) = bytes_read else {
+            return false as _;
+        };
+        *bytes_read = 0;
+        if data_out.is_null() || bytes_to_read <= 0 {
+            return false as _;
+        }
+        let Ok(mut slot) = self.response.lock() else {
+            return false as _;
+        };
+        let Some(response) = slot.as_mut() else {
+            return false as _;
+        };
+        let buffer = unsafe { std::slice::from_raw_parts_mut(data_out, bytes_to_read as usize) };
+        match response.body.read(buffer) {
+            Ok(0) => false as _,
+            Ok(n) => {
+                *bytes_read = n as _;
+                true as _
+            }
+            Err(err) => {
+                tracing::error!(?err, "cannot read custom scheme body");
+                *bytes_read = sys::cef_errorcode_t::ERR_FAILED as _;
+                false as _
+            }
+        }
+    }
+
+    fn cancel(&self) {
+        if let Ok(mut slot) = self.response.lock() {
+            slot.take();
+        }
+    }
+}
@@ -0,0 +1,278 @@
+//! Capturing the rendered page as an rgba image
+
+use base64::Engine;
+use iced_wgpu::window::compositor::hack_wgpu::{get_wgpu_device, get_wgpu_queue};
+use tokio::sync::oneshot;
+
+use crate::{IcyClientState, devtools::IcyDevToolsState, error::ScreenshotError, geometry};
+
+/// Part of the page to capture
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ScreenshotArea {
+    /// The visible part of the page
+    #[default]
+    Viewport,
+    /// A rectangle of the viewport in logical pixels
+    Region(iced::Rectangle),
+    /// The whole scrollable document, rendered by DevTools `Page.captureScreenshot`
+    FullPage,
+}
+
+/// 8-bit rgba image in physical pixels
+#[derive(Clone)]
+pub struct Screenshot {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl std::fmt::Debug for Screenshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Screenshot")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish()
+    }
+}
+
+impl Screenshot {
+    fn from_bgra(width: u32, height: u32, mut pixels: Vec<u8>) -> Self {
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Cut out `region`, clamped to the image
+    fn crop(self, region: iced::Rectangle<u32>) -> Self {
+        let x = region.x.min(self.width);
+        let y = region.y.min(self.height);
+        let width = region.width.min(self.width - x);
+        let height = region.height.min(self.height - y);
+        let stride = self.width as usize * 4;
+        let pixels = (y..y + height)
+            .flat_map(|row| {
+                let start = row as usize * stride + x as usize * 4;
+                self.pixels[start..start + width as usize * 4]
+                    .iter()
+                    .copied()
+            })
+            .collect();
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn into_handle(self) -> iced::widget::image::Handle {
+        iced::widget::image::Handle::from_rgba(self.width, self.height, self.pixels)
+    }
+}
+
+/// The latest frame, from the `on_paint` buffer or read back from the copy of the shared
+/// texture, whichever path painted last
+pub(crate) fn capture_frame(
+    state: &IcyClientState,
+    region: Option<iced::Rectangle>,
+) -> iced::Task<Result<Screenshot, ScreenshotError>> {
+    let render = &state.render;
+    let region =
+        region.map(|region| geometry::view_to_device(region, render.device_scale_factor()));
+    let crop = move |screenshot: Screenshot| match region {
+        Some(region) => screenshot.crop(region),
+        None => screenshot,
+    };
+    let (width, height) = render.size();
+    if width > 0 && height > 0 && !render.pixels().is_empty() {
+        let screenshot = Screenshot::from_bgra(width as _, height as _, render.pixels().clone());
+        return iced::Task::done(Ok(crop(screenshot)));
+    }
+    let Some(texture) = render.texture() else {
+        return iced::Task::done(Err(ScreenshotError::NoFrame));
+    };
+    let readback = read_texture(&texture);
+    iced::Task::future(async move { readback?.await.map(crop) })
+}
+
+/// Copy `texture` into a buffer and resolve once it is mapped, without blocking the caller
+fn read_texture(
+    texture: &wgpu::Texture,
+) -> Result<impl Future<Output = Result<Screenshot, ScreenshotError>> + use<>, ScreenshotError> {
+    let (Some(device), Some(queue)) = (get_wgpu_device(), get_wgpu_queue()) else {
+        return Err(ScreenshotError::Readback("no wgpu device".into()));
+    };
+    let swap_red_blue = match texture.format() {
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+        format => {
+            return Err(ScreenshotError::Readback(format!(
+                "unsupported texture format {format:?}"
+            )));
+        }
+    };
+    let (width, height) = (texture.width(), texture.height());
+    let unpadded_bytes_per_row = width * 4;
+    let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+        * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Cef Screenshot Buffer"),
+        size: (padded_bytes_per_row * height) as _,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Cef Screenshot Command"),
+    });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        texture.size(),
+    );
+    queue.submit(Some(encoder.finish()));
+
+    let (tx, rx) = oneshot::channel();
+    buffer
+        .slice(..)
+        .map_async(wgpu::MapMode::Read, move |result| {
+            _ = tx.send(result);
+        });
+    // the mapping completes on a device poll, wait for it away from the ui thread
+    let device = device.clone();
+    std::thread::spawn(move || {
+        if let Err(err) = device.poll(wgpu::PollType::Wait) {
+            tracing::warn!(?err, "cannot poll the wgpu device for a screenshot");
+        }
+    });
+
+    Ok(async move {
+        rx.await
+            .map_err(|_| ScreenshotError::Readback("mapping the buffer was cancelled".into()))?
+            .map_err(|err| ScreenshotError::Readback(err.to_string()))?;
+
+        let slice = buffer.slice(..);
+        let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as _);
+        for row in slice
+            .get_mapped_range()
+            .chunks_exact(padded_bytes_per_row as _)
+        {
+            pixels.extend_from_slice(&row[..unpadded_bytes_per_row as _]);
+        }
+        buffer.unmap();
+
+        Ok(if swap_red_blue {
+            Screenshot::from_bgra(width, height, pixels)
+        } else {
+            Screenshot {
+                width,
+                height,
+                pixels,
+            }
+        })
+    })
+}
+
+/// Measure the document, then capture all of it with DevTools in physical pixels
+pub(crate) fn capture_full_page(
+    devtools: &IcyDevToolsState,
+    host: &cef::BrowserHost,
+    device_scale_factor: f32,
+) -> oneshot::Receiver<Result<Screenshot, ScreenshotError>> {
+    let (tx, rx) = oneshot::channel();
+    let state = devtools.clone();
+    devtools.execute(
+        host,
+        "Page.getLayoutMetrics",
+        serde_json::json!({}),
+        move |host, metrics| {
+            let size = metrics.and_then(|metrics| {
+                let size = &metrics["cssContentSize"];
+                match (size["width"].as_f64(), size["height"].as_f64()) {
+                    (Some(width), Some(height)) => Ok((width, height)),
+                    _ => Err("no content size in layout metrics".to_string()),
+                }
+            });
+            let (width, height) = match size {
+                Ok(size) => size,
+                Err(err) => {
+                    _ = tx.send(Err(ScreenshotError::DevTools(err)));
+                    return;
+                }
+            };
+            state.execute(
+                host,
+                "Page.captureScreenshot",
+                serde_json::json!({
+                    "format": "png",
+                    "captureBeyondViewport": true,
+                    "clip": {
+                        "x": 0,
+                        "y": 0,
+                        "width": width,
+                        "height": height,
+                        "scale": device_scale_factor,
+                    },
+                }),
+                move |_host, result| {
+                    let screenshot = result
+                        .map_err(ScreenshotError::DevTools)
+                        .and_then(|result| match result["data"].as_str() {
+                            Some(data) => decode_png(data),
+                            None => Err(ScreenshotError::DevTools("no screenshot data".into())),
+                        });
+                    _ = tx.send(screenshot);
+                },
+            );
+        },
+    );
+    rx
+}
+
+fn decode_png(data: &str) -> Result<Screenshot, ScreenshotError> {
+    let decode_error = |err: &dyn std::fmt::Display| ScreenshotError::Decode(err.to_string());
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .map_err(|err| decode_error(&err))?;
+    let mut decoder = png::Decoder::new(std::io::Cursor::new(bytes));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|err| decode_error(&err))?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buffer)
+        .map_err(|err| decode_error(&err))?;
+    buffer.truncate(info.buffer_size());
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => buffer,
+        png::ColorType::Rgb => buffer
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], u8::MAX])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buffer
+            .chunks_exact(2)
+            .flat_map(|ga| [ga[0], ga[0], ga[0], ga[1]])
+            .collect(),
+        png::ColorType::Grayscale => buffer.iter().flat_map(|g| [*g, *g, *g, u8::MAX]).collect(),
+        png::ColorType::Indexed => {
+            return Err(ScreenshotError::Decode(
+                "indexed png was not expanded".into(),
+            ));
+        }
+    };
+    Ok(Screenshot {
+        width: info.width,
+        height: info.height,
+        pixels,
+    })
+}
@@ -1,45 +1,304 @@
 use camino::Utf8PathBuf;
+use cef::{ImplCommandLine, sys};
+use std::collections::BTreeMap;
 
-#[derive(Debug, Default)]
+/// Chromium switches appended to the browser process command line, chromium passes
+/// the ones child processes need on to them
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandLineSwitches {
+    switches: BTreeMap<String, Option<String>>,
+}
+
+impl Default for CommandLineSwitches {
+    fn default() -> Self {
+        Self::secure()
+    }
+}
+
+impl CommandLineSwitches {
+    /// No switches at all
+    pub fn empty() -> Self {
+        Self {
+            switches: BTreeMap::new(),
+        }
+    }
+
+    /// Only switches hiding desktop browser UI that makes no sense for an embedded view
+    pub fn secure() -> Self {
+        [
+            "hide-crash-restore-bubble",
+            "disable-session-crashed-bubble",
+            "disable-chrome-login-prompt",
+            "no-startup-window",
+            "noerrdialogs",
+            "disable-spell-checking",
+        ]
+        .into_iter()
+        .fold(Self::empty(), Self::add)
+    }
+
+    /// [`Self::secure`] plus web security off, certificate errors ignored and the mock
+    /// keychain instead of the system one. Never ship this.
+    ///
+    /// Remote debugging is enabled with [`crate::CefSettings::remote_debugging_port`].
+    pub fn development() -> Self {
+        [
+            "disable-web-security",
+            "allow-running-insecure-content",
+            "ignore-certificate-errors",
+            "ignore-ssl-errors",
+            "use-mock-keychain",
+        ]
+        .into_iter()
+        .fold(Self::secure(), Self::add)
+    }
+
+    /// Add a switch without value, the leading `--` is omitted
+    pub fn add(mut self, name: impl Into<String>) -> Self {
+        self.switches.insert(name.into(), None);
+        self
+    }
+
+    pub fn add_with_value(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.switches.insert(name.into(), Some(value.into()));
+        self
+    }
+
+    pub fn remove(mut self, name: &str) -> Self {
+        self.switches.remove(name);
+        self
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.switches.contains_key(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.switches
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_deref()))
+    }
+
+    pub(crate) fn apply(&self, command_line: &mut cef::CommandLine) {
+        for (name, value) in self.iter() {
+            match value {
+                Some(value) => {
+                    command_line.append_switch_with_value(Some(&name.into()), Some(&value.into()))
+                }
+                None => command_line.append_switch(Some(&name.into())),
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogSeverity {
+    #[default]
+    Default,
+    Verbose,
+    Info,
+    Warning,
+    Error,
+    Fatal,
+    Disable,
+}
+
+impl From<LogSeverity> for cef::LogSeverity {
+    fn from(severity: LogSeverity) -> Self {
+        match severity {
+            LogSeverity::Default => sys::cef_log_severity_t::LOGSEVERITY_DEFAULT,
+            LogSeverity::Verbose => sys::cef_log_severity_t::LOGSEVERITY_VERBOSE,
+            LogSeverity::Info => sys::cef_log_severity_t::LOGSEVERITY_INFO,
+            LogSeverity::Warning => sys::cef_log_severity_t::LOGSEVERITY_WARNING,
+            LogSeverity::Error => sys::cef_log_severity_t::LOGSEVERITY_ERROR,
+            LogSeverity::Fatal => sys::cef_log_severity_t::LOGSEVERITY_FATAL,
+            LogSeverity::Disable => sys::cef_log_severity_t::LOGSEVERITY_DISABLE,
+        }
+        .into()
+    }
+}
+
+/// Global settings passed to [`crate::init_cef_with`]
+#[derive(Clone, Debug)]
 pub struct CefSettings {
     cache_path: Utf8PathBuf,
+    root_cache_path: Utf8PathBuf,
     user_agent: String,
+    user_agent_product: String,
     locale: Option<String>,
+    accept_languages: Option<String>,
     log_file_path: Utf8PathBuf,
+    log_severity: LogSeverity,
+    resources_dir_path: Utf8PathBuf,
+    locales_dir_path: Utf8PathBuf,
+    background_color: iced::Color,
+    remote_debugging_port: Option<u16>,
+    persist_session_cookies: bool,
+    switches: CommandLineSwitches,
+}
+
+impl Default for CefSettings {
+    fn default() -> Self {
+        Self {
+            cache_path: Utf8PathBuf::new(),
+            root_cache_path: Utf8PathBuf::new(),
+            user_agent: String::new(),
+            user_agent_product: String::new(),
+            locale: None,
+            accept_languages: None,
+            log_file_path: Utf8PathBuf::new(),
+            log_severity: LogSeverity::Default,
+            resources_dir_path: Utf8PathBuf::new(),
+            locales_dir_path: Utf8PathBuf::new(),
+            background_color: iced::Color::WHITE,
+            remote_debugging_port: None,
+            persist_session_cookies: true,
+            switches: CommandLineSwitches::secure(),
+        }
+    }
 }
 
-#[allow(unused)]
 impl CefSettings {
     pub fn new() -> Self {
         Self::default()
     }
 
-    pub fn cache_path(mut self, path: Utf8PathBuf) -> Self {
-        self.cache_path = path;
+    /// Directory of the global browser cache, in-memory if empty.
+    /// Must be equal to or a child of [`Self::root_cache_path`].
+    pub fn cache_path(mut self, path: impl Into<Utf8PathBuf>) -> Self {
+        self.cache_path = path.into();
+        self
+    }
+
+    /// Common parent directory of all cache directories
+    pub fn root_cache_path(mut self, path: impl Into<Utf8PathBuf>) -> Self {
+        self.root_cache_path = path.into();
         self
     }
 
-    pub fn user_agent(mut self, agent: String) -> Self {
-        self.user_agent = agent;
+    /// Replaces the whole default user agent
+    pub fn user_agent(mut self, agent: impl Into<String>) -> Self {
+        self.user_agent = agent.into();
         self
     }
 
+    /// Product part of the default user agent, ignored if [`Self::user_agent`] is set
+    pub fn user_agent_product(mut self, product: impl Into<String>) -> Self {
+        self.user_agent_product = product.into();
+        self
+    }
+
+    /// Locale of the browser UI strings, e.g. `en-US`, the system locale if unset
+    pub fn locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+
+    /// Comma separated `Accept-Language` list, defaults to the locale or `en-US`
+    pub fn accept_languages(mut self, languages: impl Into<String>) -> Self {
+        self.accept_languages = Some(languages.into());
+        self
+    }
+
+    pub fn log_file_path(mut self, path: impl Into<Utf8PathBuf>) -> Self {
+        self.log_file_path = path.into();
+        self
+    }
+
+    pub fn log_severity(mut self, severity: LogSeverity) -> Self {
+        self.log_severity = severity;
+        self
+    }
+
+    pub fn resources_dir_path(mut self, path: impl Into<Utf8PathBuf>) -> Self {
+        self.resources_dir_path = path.into();
+        self
+    }
+
+    pub fn locales_dir_path(mut self, path: impl Into<Utf8PathBuf>) -> Self {
+        self.locales_dir_path = path.into();
+        self
+    }
+
+    /// Painted before the page draws its own background
+    pub fn background_color(mut self, color: iced::Color) -> Self {
+        self.background_color = color;
+        self
+    }
+
+    /// Serve the devtools protocol on `port`, takes precedence over a `remote-debugging-port`
+    /// switch
+    pub fn remote_debugging_port(mut self, port: u16) -> Self {
+        self.remote_debugging_port = Some(port);
+        self
+    }
+
+    /// Keep session cookies(no expiry) across restarts, requires a cache path
+    pub fn persist_session_cookies(mut self, persist: bool) -> Self {
+        self.persist_session_cookies = persist;
+        self
+    }
+
+    /// Chromium switches, [`CommandLineSwitches::secure`] by default
+    pub fn switches(mut self, switches: CommandLineSwitches) -> Self {
+        self.switches = switches;
+        self
+    }
+
+    /// Parent of all cache directories, CEF falls back to the cache path if unset
+    pub(crate) fn effective_root_cache_path(&self) -> &Utf8PathBuf {
+        if self.root_cache_path.as_str().is_empty() {
+            &self.cache_path
+        } else {
+            &self.root_cache_path
+        }
+    }
+
+    /// [`Self::remote_debugging_port`] wins over a `remote-debugging-port` switch
+    pub(crate) fn command_line_switches(&self) -> CommandLineSwitches {
+        match self.remote_debugging_port {
+            Some(_) => self.switches.clone().remove("remote-debugging-port"),
+            None => self.switches.clone(),
+        }
+    }
+
     pub(crate) fn into_cef_settings(self) -> cef::Settings {
         let Self {
             cache_path,
+            root_cache_path,
             user_agent,
+            user_agent_product,
             locale,
+            accept_languages,
             log_file_path,
+            log_severity,
+            resources_dir_path,
+            locales_dir_path,
+            background_color,
+            remote_debugging_port,
+            persist_session_cookies,
             ..
         } = self;
+        let accept_languages = accept_languages
+            .or_else(|| locale.clone())
+            .unwrap_or("en-US".into());
+        let [r, g, b, a] = background_color.into_rgba8();
         let settings = cef::Settings {
-            persist_session_cookies: true as _,
+            persist_session_cookies: persist_session_cookies as _,
             cache_path: cache_path.as_str().into(),
+            root_cache_path: root_cache_path.as_str().into(),
             user_agent: user_agent.as_str().into(),
+            user_agent_product: user_agent_product.as_str().into(),
             windowless_rendering_enabled: true as _,
             external_message_pump: true as _,
-            accept_language_list: locale.unwrap_or("en-US".into()).as_str().into(),
+            locale: locale.unwrap_or_default().as_str().into(),
+            accept_language_list: accept_languages.as_str().into(),
             log_file: log_file_path.as_str().into(),
+            log_severity: log_severity.into(),
+            resources_dir_path: resources_dir_path.as_str().into(),
+            locales_dir_path: locales_dir_path.as_str().into(),
+            background_color: u32::from_be_bytes([a, r, g, b]),
+            remote_debugging_port: remote_debugging_port.unwrap_or_default() as _,
             no_sandbox: true as _,
             ..Default::default()
         };
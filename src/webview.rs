@@ -1,6 +1,5 @@
-use super::instance::{get_cursor_type, resize};
-use crate::BrowserId;
-use cef::{ImplBrowser, ImplBrowserHost};
+use crate::{IcyClientState, geometry};
+use cef::ImplBrowserHost;
 use iced::{self};
 use iced::{
     Element, Event, Length, Rectangle, Renderer, Size, Theme,
@@ -17,15 +16,14 @@ use iced_core::{self, input_method};
 
 struct CefState {
     bounds: iced::Rectangle,
-    browser_id: BrowserId,
-    browser_host: Option<cef::BrowserHost>,
+    /// Last cursor position sent to the page, `None` once it left
+    hovered: Option<iced::Point>,
+    /// A button was pressed inside the bounds and is still held, the drag stays with this view
+    grabbed: bool,
 }
 
 impl CefState {
-    fn resize(&mut self, bound: iced::Rectangle) {
-        resize(self.browser_id, bound);
-    }
-
+    /// `cursor` and `focused_node` are in view coordinates, `bound` places the view in the window
     fn input_method(
         &self,
         cursor: Option<iced::Point>,
@@ -33,13 +31,12 @@ impl CefState {
         focused_node: Option<iced::Rectangle>,
         caret_offset: Option<f32>,
     ) -> iced_core::InputMethod {
-        if let Some(p) = focused_node.map(|n| n.position()).or(cursor)
-            && let Some(offset) = caret_offset
+        // the caret offset counts characters, it only tells that a text field has a caret
+        if let Some(node) = focused_node.or(cursor.map(geometry::point_rect))
+            && caret_offset.is_some()
         {
-            let x = p.x + bound.x;
-            let y = p.y + bound.y + 8.0;
             return iced_core::input_method::InputMethod::Enabled {
-                position: iced::Point::new(x + offset, y),
+                position: geometry::ime_position(node, bound),
                 purpose: iced_core::input_method::Purpose::Normal,
                 preedit: None,
             };
@@ -49,31 +46,42 @@ impl CefState {
 }
 
 pub struct Webview<'a, Message> {
-    browser_id: BrowserId,
+    host: Option<cef::BrowserHost>,
+    client: Option<IcyClientState>,
     content: Element<'a, Message, Theme, Renderer>,
     focused_node: Option<iced::Rectangle>,
     caret_offset: Option<f32>,
+    focused: bool,
+    on_focus: Option<Box<dyn Fn(bool) -> Message + 'a>>,
     on_input_method_event: Option<Box<dyn Fn(input_method::Event) -> Message + 'a>>,
     on_key_event: Option<Box<dyn Fn(keyboard::Event) -> Message + 'a>>,
     on_mouse_event: Option<Box<dyn Fn(iced::Point, mouse::Event) -> Message + 'a>>,
 }
 
 impl<'a, Message> Webview<'a, Message> {
-    pub fn new(
-        browser_id: BrowserId,
-        content: impl Into<Element<'a, Message, Theme, Renderer>>,
-    ) -> Self {
+    pub fn new(content: impl Into<Element<'a, Message, Theme, Renderer>>) -> Self {
         Webview {
-            browser_id,
+            host: None,
+            client: None,
             content: content.into(),
             focused_node: None,
             caret_offset: None,
+            focused: true,
+            on_focus: None,
             on_input_method_event: None,
             on_key_event: None,
             on_mouse_event: None,
         }
     }
 
+    /// The browser drawn by `content`, the webview keeps its size and scale in sync with the
+    /// widget and is the only one resizing it
+    pub fn browser(mut self, host: cef::BrowserHost, client: IcyClientState) -> Self {
+        self.host = Some(host);
+        self.client = Some(client);
+        self
+    }
+
     pub fn focused_node(mut self, focused_node: Option<iced::Rectangle>) -> Self {
         self.focused_node = focused_node;
         self
@@ -84,6 +92,19 @@ impl<'a, Message> Webview<'a, Message> {
         self
     }
 
+    /// Only a focused webview receives keyboard and input method events, on by default
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    /// Called with `true` on a click into an unfocused webview and with `false` on a click
+    /// outside of a focused one
+    pub fn on_focus(mut self, on_focus: impl Fn(bool) -> Message + 'a) -> Self {
+        self.on_focus = Some(Box::new(on_focus));
+        self
+    }
+
     pub fn on_input_method_event(
         mut self,
         on_input_method_event: impl Fn(input_method::Event) -> Message + 'a,
@@ -116,10 +137,9 @@ where
 
     fn state(&self) -> tree::State {
         tree::State::new(CefState {
-            browser_id: self.browser_id,
             bounds: iced::Rectangle::with_size(iced::Size::ZERO),
-            browser_host: cef::browser_host_get_browser_by_identifier(self.browser_id.inner())
-                .and_then(|browser| browser.host()),
+            hovered: None,
+            grabbed: false,
         })
     }
 
@@ -182,39 +202,74 @@ where
     ) {
         let state = tree.state.downcast_mut::<CefState>();
         let bounds = layout.bounds();
-        if state.bounds != bounds {
-            state.bounds = bounds.into();
-            state.resize(bounds);
+        if state.bounds != bounds
+            && let (Some(host), Some(client)) = (&self.host, &self.client)
+        {
+            state.bounds = bounds;
+            client.render.resize(host, bounds);
         }
 
         match event {
             Event::Keyboard(event) => {
-                if let Some(on_key_event) = &self.on_key_event {
+                if self.focused
+                    && let Some(on_key_event) = &self.on_key_event
+                {
                     shell.publish(on_key_event(event.clone()));
                     shell.capture_event();
                 }
             }
             Event::Mouse(event) => {
-                if let Some(point) = cursor.position_in(bounds)
-                    && let Some(on_mouse_event) = &self.on_mouse_event
-                {
-                    shell.publish(on_mouse_event(point, event.clone()));
-                    shell.capture_event();
+                let inside = cursor.position_in(bounds);
+                if let mouse::Event::ButtonPressed(_) = event {
+                    state.grabbed = inside.is_some();
+                    if inside.is_some() != self.focused
+                        && let Some(on_focus) = &self.on_focus
+                    {
+                        shell.publish(on_focus(inside.is_some()));
+                    }
+                }
+                // keep delivering a drag that started inside, relative to this view
+                let grabbed = state.grabbed;
+                let point = inside.or_else(|| {
+                    cursor.position().filter(|_| grabbed).map(|position| {
+                        iced::Point::new(position.x - bounds.x, position.y - bounds.y)
+                    })
+                });
+                if let mouse::Event::ButtonReleased(_) = event {
+                    state.grabbed = false;
+                }
+                if let Some(on_mouse_event) = &self.on_mouse_event {
+                    if let Some(point) = point {
+                        shell.publish(on_mouse_event(point, event.clone()));
+                        shell.capture_event();
+                    } else if let Some(last) = state.hovered {
+                        shell.publish(on_mouse_event(last, mouse::Event::CursorLeft));
+                    }
+                }
+                state.hovered = point;
+            }
+            Event::Window(iced::window::Event::Rescaled(scale_factor)) => {
+                if let (Some(host), Some(client)) = (&self.host, &self.client) {
+                    client.render.rescale(host, *scale_factor);
                 }
             }
             Event::Window(iced::window::Event::RedrawRequested(_now)) => {
-                if let Some(browser_host) = &state.browser_host {
-                    browser_host.send_external_begin_frame();
+                if let Some(host) = &self.host {
+                    host.send_external_begin_frame();
+                }
+                if self.focused {
+                    shell.request_input_method::<String>(&state.input_method(
+                        cursor.position_in(bounds),
+                        bounds,
+                        self.focused_node,
+                        self.caret_offset,
+                    ));
                 }
-                shell.request_input_method::<String>(&state.input_method(
-                    cursor.position_in(bounds),
-                    bounds,
-                    self.focused_node,
-                    self.caret_offset,
-                ));
             }
             Event::InputMethod(event) => {
-                if let Some(on_input_method_event) = &self.on_input_method_event {
+                if self.focused
+                    && let Some(on_input_method_event) = &self.on_input_method_event
+                {
                     shell.publish(on_input_method_event(event.clone()));
                     shell.request_redraw();
                     shell.capture_event();
@@ -253,10 +308,10 @@ where
         _viewport: &Rectangle,
         _renderer: &Renderer,
     ) -> mouse::Interaction {
-        if cursor.position_in(layout.bounds()).is_some() {
-            if let Some(cursor_type) = get_cursor_type(self.browser_id) {
-                return map_cursor(cursor_type);
-            }
+        if cursor.position_in(layout.bounds()).is_some()
+            && let Some(client) = &self.client
+        {
+            return map_cursor(client.display.cursor_type.borrow().clone());
         }
         return mouse::Interaction::None;
 
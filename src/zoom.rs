@@ -0,0 +1,111 @@
+//! Page zoom factors and the keyboard shortcuts that step through them
+
+use iced::keyboard::{self, Key};
+
+/// Factors stepped through by [`crate::CefMessage::ZoomIn`] and [`crate::CefMessage::ZoomOut`],
+/// the same presets as Chrome.
+pub const ZOOM_FACTORS: [f64; 15] = [
+    0.25, 0.33, 0.5, 0.67, 0.75, 0.8, 0.9, 1.0, 1.1, 1.25, 1.5, 1.75, 2.0, 2.5, 3.0,
+];
+
+/// Chromium scales by `1.2^level`
+const ZOOM_BASE: f64 = 1.2;
+
+pub(crate) fn factor_to_level(factor: f64) -> f64 {
+    factor.ln() / ZOOM_BASE.ln()
+}
+
+pub(crate) fn clamp_factor(factor: f64) -> f64 {
+    factor.clamp(ZOOM_FACTORS[0], ZOOM_FACTORS[ZOOM_FACTORS.len() - 1])
+}
+
+/// Nearest preset above `factor`
+pub(crate) fn zoom_in(factor: f64) -> f64 {
+    ZOOM_FACTORS
+        .into_iter()
+        .find(|preset| *preset > factor + f64::EPSILON)
+        .unwrap_or(ZOOM_FACTORS[ZOOM_FACTORS.len() - 1])
+}
+
+/// Nearest preset below `factor`
+pub(crate) fn zoom_out(factor: f64) -> f64 {
+    ZOOM_FACTORS
+        .into_iter()
+        .rev()
+        .find(|preset| *preset < factor - f64::EPSILON)
+        .unwrap_or(ZOOM_FACTORS[0])
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ZoomShortcut {
+    In,
+    Out,
+    Reset,
+}
+
+/// Ctrl(Cmd on macOS) with `+`, `=`, `-` or `0`
+pub(crate) fn shortcut(key: &Key, modifiers: keyboard::Modifiers) -> Option<ZoomShortcut> {
+    if !modifiers.command() || modifiers.alt() {
+        return None;
+    }
+    match key.as_ref() {
+        Key::Character("+" | "=") => Some(ZoomShortcut::In),
+        Key::Character("-") => Some(ZoomShortcut::Out),
+        Key::Character("0") => Some(ZoomShortcut::Reset),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_through_presets() {
+        let cases = [
+            (1.0, 1.1, 0.9),
+            (1.05, 1.1, 1.0),
+            (0.25, 0.33, 0.25),
+            (3.0, 3.0, 2.5),
+            (0.1, 0.25, 0.25),
+            (5.0, 3.0, 3.0),
+        ];
+        for (factor, zoomed_in, zoomed_out) in cases {
+            assert_eq!(zoom_in(factor), zoomed_in, "in from {factor}");
+            assert_eq!(zoom_out(factor), zoomed_out, "out from {factor}");
+        }
+    }
+
+    #[test]
+    fn clamps_to_presets() {
+        let cases = [(0.1, 0.25), (1.3, 1.3), (4.0, 3.0)];
+        for (factor, expected) in cases {
+            assert_eq!(clamp_factor(factor), expected, "{factor}");
+        }
+    }
+
+    #[test]
+    fn level_of_default_factor_is_zero() {
+        assert_eq!(factor_to_level(1.0), 0.0);
+        assert!((factor_to_level(1.2) - 1.0).abs() < 1e-9);
+        assert!((factor_to_level(1.0 / 1.2) + 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn shortcuts() {
+        let character = |c: &str| Key::Character(c.into());
+        let command = keyboard::Modifiers::COMMAND;
+        let cases = [
+            (character("+"), command, Some(ZoomShortcut::In)),
+            (character("="), command, Some(ZoomShortcut::In)),
+            (character("-"), command, Some(ZoomShortcut::Out)),
+            (character("0"), command, Some(ZoomShortcut::Reset)),
+            (character("0"), keyboard::Modifiers::empty(), None),
+            (character("0"), command | keyboard::Modifiers::ALT, None),
+            (character("a"), command, None),
+        ];
+        for (key, modifiers, expected) in cases {
+            assert_eq!(shortcut(&key, modifiers), expected, "{key:?} {modifiers:?}");
+        }
+    }
+}